@@ -1,5 +1,6 @@
 extern crate fuzz_targets;
-use fuzz_targets::fuzz_###TARGET### as fuzz_target;
+use fuzz_targets::debug_###TARGET### as debug_target;
+use fuzz_targets::Outcome;
 
 use std::env;
 use std::fs::File;
@@ -32,8 +33,13 @@ fn main() {
     // read data from provided file
     let data = read_contents_from_path(&args[1]).expect("cannot read file content");
 
-    // call the fuzzing target
-    fuzz_target(&data);
+    // call the debug target
+    let outcome = debug_target(&data);
+    println!("outcome: {}", outcome);
 
+    if let Outcome::Disagreement { .. } = outcome {
+        println!("No crash, but implementations disagree\n");
+        return;
+    }
     println!("No crash, everything is OK\n");
 }
@@ -29,11 +29,11 @@ fn log_benchmark(it: Instant) {
     println!("{}", format!("benchmark (sec): {:5.10}", elapsed / 1024.).yellow());
 }
 
-fn is_ok(target: String, res: bool) {
-    if res {
-        println!("{}", format!("[O] {}: Ok()", target).green());
-    } else {
-        println!("{}", format!("[X] {}: Err()", target).red());
+fn is_ok(target: String, res: Outcome) {
+    match res {
+        Outcome::Accepted => println!("{}", format!("[O] {}: Ok()", target).green()),
+        Outcome::Unsupported => println!("{}", format!("[-] {}: {}", target, res).yellow()),
+        _ => println!("{}", format!("[X] {}: {}", target, res).red()),
     }
 }
 
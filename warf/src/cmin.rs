@@ -0,0 +1,78 @@
+use failure::{Error, ResultExt};
+use std::fs;
+use std::path::Path;
+
+use crate::env::workspace_dir;
+use crate::fuzzers::{get_fuzzer, FuzzerConfig};
+use crate::targets::{find_target, kind_corpus_dir, target_corpus_dir};
+
+/// Number of inputs inside a corpus
+fn count_inputs(dir: &Path) -> Result<usize, Error> {
+    let mut count = 0;
+    for entry in fs::read_dir(dir).context(format!("error reading {}", dir.display()))? {
+        if entry?.path().is_file() {
+            count += 1;
+        }
+    }
+    Ok(count)
+}
+
+/// Replace the inputs of the corpus `dest` by the ones of `src`
+fn replace_corpus(src: &Path, dest: &Path) -> Result<(), Error> {
+    if dest.is_dir() {
+        for entry in fs::read_dir(dest)? {
+            let path = entry?.path();
+            if path.is_file() {
+                fs::remove_file(&path)?;
+            }
+        }
+    }
+    fs::create_dir_all(dest).context(format!("unable to create {} dir", dest.display()))?;
+    for entry in fs::read_dir(src)? {
+        let entry = entry?;
+        if entry.path().is_file() {
+            fs::copy(entry.path(), dest.join(entry.file_name()))?;
+        }
+    }
+    Ok(())
+}
+
+/// Minimize the corpus of the target with the given fuzzer.
+///
+/// The reduced corpus replaces the own corpus of the target, or with
+/// `in_place` the corpora shared by all the targets of its type.
+pub fn run_cmin(target: String, fuzzer: String, in_place: bool) -> Result<(), Error> {
+    let target = find_target(&target)?;
+    let fuzzer = get_fuzzer(&fuzzer, FuzzerConfig::default())?;
+
+    let input = if in_place {
+        kind_corpus_dir(target)?
+    } else {
+        target_corpus_dir(target)?
+    };
+    let output = workspace_dir()?.join("cmin").join(target.name());
+    if output.exists() {
+        fs::remove_dir_all(&output).context(format!("error removing {}", output.display()))?;
+    }
+    fuzzer.cmin(target, &input, &output)?;
+
+    let before = count_inputs(&input)?;
+    let after = count_inputs(&output)?;
+    if after == 0 {
+        bail!(
+            "corpus minimization of {} gave no input, {} left untouched",
+            target.name(),
+            input.display()
+        );
+    }
+
+    replace_corpus(&output, &input)?;
+    fs::remove_dir_all(&output)?;
+    println!(
+        "[WARF] Cmin: {} inputs -> {} inputs, saved in {}",
+        before,
+        after,
+        input.display()
+    );
+    Ok(())
+}
@@ -0,0 +1,268 @@
+use failure::{Error, ResultExt};
+use std::collections::BTreeMap;
+use std::env;
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+use crate::debug::compile_debug_target;
+use crate::env::workspace_dir;
+use crate::targets::{find_target, target_corpus_dir, Targets};
+use crate::utils::{host_triple, wait_timeout};
+
+/// Maximum time to replay one input, fuzzers already report the hangs
+const REPLAY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Source files of the toolchain, not part of the report
+const IGNORED_FILES: &str = r"/rustc/|/\.rustup/";
+
+/// Directory where the coverage reports of the target are saved
+fn coverage_dir(target: Targets) -> Result<PathBuf, Error> {
+    Ok(workspace_dir()?.join("coverage").join(target.name()))
+}
+
+/// Output of `rustc` called with the given arguments
+fn rustc_output(args: &[&str]) -> Result<String, Error> {
+    let output = Command::new("rustc")
+        .args(args)
+        .output()
+        .context("error running rustc")?;
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Path of a LLVM tool shipped with the Rust toolchain (e.g. `llvm-cov`)
+fn llvm_tool(name: &str) -> Result<PathBuf, Error> {
+    let sysroot = rustc_output(&["--print", "sysroot"])?;
+    let host = host_triple()?;
+    let path = Path::new(&sysroot)
+        .join("lib")
+        .join("rustlib")
+        .join(&host)
+        .join("bin")
+        .join(name);
+    if !path.is_file() {
+        bail!(
+            "{} not available, install with `rustup component add llvm-tools-preview`",
+            name
+        );
+    }
+    Ok(path)
+}
+
+/// Run the instrumented binary on every input of the corpus, the
+/// profiles of all the executions are merged into `profraw_dir`.
+///
+/// Crashes are expected, the profile is written anyway.
+fn replay_corpus(bin: &Path, corpus: &Path, profraw_dir: &Path) -> Result<usize, Error> {
+    let mut replayed = 0;
+    for entry in fs::read_dir(corpus).context(format!("error reading {}", corpus.display()))? {
+        let input = entry?.path();
+        if !input.is_file() {
+            continue;
+        }
+        let mut child = Command::new(bin)
+            .arg(&input)
+            .env("LLVM_PROFILE_FILE", profraw_dir.join("%m.profraw"))
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .context(format!("error replaying {}", input.display()))?;
+
+        if wait_timeout(&mut child, REPLAY_TIMEOUT)?.is_none() {
+            println!("[WARF] Coverage: timeout on {}", input.display());
+        }
+        replayed += 1;
+    }
+    Ok(replayed)
+}
+
+/// Name of the crate owning the source file, read from the closest
+/// `Cargo.toml` (works for the registry, git checkouts and the
+/// workspace, including the C/C++ code built by `-sys` crates)
+fn crate_of(source: &Path) -> String {
+    let package = regex!(r#"(?s)\[package\].*?\nname\s*=\s*"([^"]+)""#);
+    for dir in source.ancestors().skip(1) {
+        if let Ok(manifest) = fs::read_to_string(dir.join("Cargo.toml")) {
+            if let Some(caps) = package.captures(&manifest) {
+                return caps[1].to_string();
+            }
+        }
+    }
+    "unknown".to_string()
+}
+
+/// Lines and functions covered inside one crate
+#[derive(Debug, Default)]
+struct Summary {
+    lines: usize,
+    lines_hit: usize,
+    functions: usize,
+    functions_hit: usize,
+}
+
+fn percent(hit: usize, total: usize) -> f64 {
+    if total == 0 {
+        return 0.0;
+    }
+    hit as f64 * 100.0 / total as f64
+}
+
+/// Group the coverage of the source files, exported in lcov format,
+/// per crate
+fn summarize(lcov: &str) -> BTreeMap<String, Summary> {
+    let mut crates: BTreeMap<String, Summary> = BTreeMap::new();
+    let mut current = Summary::default();
+    let mut source = String::new();
+    for line in lcov.lines() {
+        let (key, value) = match line.find(':') {
+            Some(i) => (&line[..i], &line[i + 1..]),
+            None => (line, ""),
+        };
+        let count = || value.parse::<usize>().unwrap_or_default();
+        match key {
+            "SF" => source = value.to_string(),
+            "LF" => current.lines = count(),
+            "LH" => current.lines_hit = count(),
+            "FNF" => current.functions = count(),
+            "FNH" => current.functions_hit = count(),
+            "end_of_record" => {
+                let summary = crates.entry(crate_of(Path::new(&source))).or_default();
+                summary.lines += current.lines;
+                summary.lines_hit += current.lines_hit;
+                summary.functions += current.functions;
+                summary.functions_hit += current.functions_hit;
+                current = Summary::default();
+            }
+            _ => {}
+        }
+    }
+    crates
+}
+
+/// Text report of the coverage of every crate
+fn format_summary(crates: &BTreeMap<String, Summary>) -> String {
+    let mut report = String::new();
+    let _ = writeln!(
+        report,
+        "{:<40} {:>24} {:>24}",
+        "crate", "lines", "functions"
+    );
+    for (name, s) in crates {
+        let _ = writeln!(
+            report,
+            "{:<40} {:>24} {:>24}",
+            name,
+            format!(
+                "{}/{} ({:.1}%)",
+                s.lines_hit,
+                s.lines,
+                percent(s.lines_hit, s.lines)
+            ),
+            format!(
+                "{}/{} ({:.1}%)",
+                s.functions_hit,
+                s.functions,
+                percent(s.functions_hit, s.functions)
+            ),
+        );
+    }
+    report
+}
+
+/// Run a LLVM tool, failing if it doesn't succeed
+fn run_llvm_tool(cmd: &mut Command, name: &str) -> Result<Vec<u8>, Error> {
+    let output = cmd
+        .stderr(Stdio::inherit())
+        .output()
+        .context(format!("error starting {}", name))?;
+    if !output.status.success() {
+        bail!("{} failed: {}", name, output.status);
+    }
+    Ok(output.stdout)
+}
+
+/// Build the target with source-based coverage, replay its corpus and
+/// save the HTML and per crate reports inside `workspace/coverage/<target>/`
+pub fn run_coverage(target: String) -> Result<(), Error> {
+    let target = find_target(&target)?;
+    let llvm_profdata = llvm_tool("llvm-profdata")?;
+    let llvm_cov = llvm_tool("llvm-cov")?;
+
+    let out_dir = coverage_dir(target)?;
+    let profraw_dir = out_dir.join("profraw");
+    // Profiles of a previous build can't be merged with the new ones
+    if profraw_dir.exists() {
+        fs::remove_dir_all(&profraw_dir)
+            .context(format!("error removing {}", profraw_dir.display()))?;
+    }
+    fs::create_dir_all(&profraw_dir)
+        .context(format!("unable to create {} dir", profraw_dir.display()))?;
+
+    // NOTE: C/C++ code of FFI targets is only covered if built by clang
+    // with `-fprofile-instr-generate -fcoverage-mapping` in CFLAGS/CXXFLAGS
+    let rust_flags = format!(
+        "-C instrument-coverage {}",
+        env::var("RUSTFLAGS").unwrap_or_default()
+    );
+    let target_dir = workspace_dir()?.join("coverage").join("target");
+    let bin = compile_debug_target(target, &target_dir, Some(rust_flags))?;
+
+    let corpus = target_corpus_dir(target)?;
+    let replayed = replay_corpus(&bin, &corpus, &profraw_dir)?;
+    println!(
+        "[WARF] Coverage: {} inputs of {} replayed",
+        replayed,
+        corpus.display()
+    );
+
+    let mut profraws = Vec::new();
+    for entry in fs::read_dir(&profraw_dir)? {
+        profraws.push(entry?.path());
+    }
+    if profraws.is_empty() {
+        bail!("no coverage profile written for {}", target.name());
+    }
+    let profdata = out_dir.join(format!("{}.profdata", target.name()));
+    run_llvm_tool(
+        Command::new(&llvm_profdata)
+            .args(["merge", "-sparse"])
+            .args(&profraws)
+            .arg("-o")
+            .arg(&profdata),
+        "llvm-profdata",
+    )?;
+
+    let html_dir = out_dir.join("html");
+    run_llvm_tool(
+        Command::new(&llvm_cov)
+            .arg("show")
+            .arg(&bin)
+            .arg(format!("-instr-profile={}", profdata.display()))
+            .arg(format!("-ignore-filename-regex={}", IGNORED_FILES))
+            .arg("-format=html")
+            .arg(format!("-output-dir={}", html_dir.display())),
+        "llvm-cov show",
+    )?;
+
+    let lcov = run_llvm_tool(
+        Command::new(&llvm_cov)
+            .arg("export")
+            .arg(&bin)
+            .arg(format!("-instr-profile={}", profdata.display()))
+            .arg(format!("-ignore-filename-regex={}", IGNORED_FILES))
+            .arg("-format=lcov")
+            .arg("-summary-only"),
+        "llvm-cov export",
+    )?;
+    let summary = format_summary(&summarize(&String::from_utf8_lossy(&lcov)));
+    fs::write(out_dir.join("summary.txt"), &summary)?;
+
+    println!("{}", summary);
+    println!(
+        "[WARF] Coverage: HTML report saved in {}",
+        html_dir.join("index.html").display()
+    );
+    Ok(())
+}
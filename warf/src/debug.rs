@@ -1,15 +1,12 @@
-use crate::strum::IntoEnumIterator;
-
 use failure::{Error, ResultExt};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
 use crate::env::root_dir;
 use crate::fuzzers::FuzzerQuit;
 
-use crate::targets::{get_targets, prepare_targets_workspace, Targets};
-use crate::utils::did_you_mean;
+use crate::targets::{find_target, prepare_targets_workspace, Targets};
 
 // TODO - simplify
 pub fn prepare_debug_workspace(out_dir: &str) -> Result<(), Error> {
@@ -37,19 +34,25 @@ pub fn prepare_debug_workspace(out_dir: &str) -> Result<(), Error> {
 // TODO - move part of this function to main.rs
 // TODO - to specific to Rust
 pub fn run_debug(target: String) -> Result<(), Error> {
-    let target = match Targets::iter().find(|x| x.name() == target) {
-        None => bail!(
-            "Don't know target `{}`. {}",
-            target,
-            if let Some(alt) = did_you_mean(&target, &get_targets()) {
-                format!("Did you mean `{}`?", alt)
-            } else {
-                "".into()
-            }
-        ),
-        Some(t) => t,
-    };
+    let target = find_target(&target)?;
+
+    build_debug_target(target)?;
+    Ok(())
+}
 
+/// Compile the debug binary of the target, returning its path.
+pub fn build_debug_target(target: Targets) -> Result<PathBuf, Error> {
+    let target_dir = root_dir()?.join("workspace").join("debug").join("target");
+    compile_debug_target(target, &target_dir, None)
+}
+
+/// Compile the debug binary of the target into `target_dir`, replacing
+/// RUSTFLAGS if `rust_flags` is given, and return its path.
+pub fn compile_debug_target(
+    target: Targets,
+    target_dir: &Path,
+    rust_flags: Option<String>,
+) -> Result<PathBuf, Error> {
     let debug_dir = root_dir()?.join("workspace").join("debug");
 
     prepare_targets_workspace()?;
@@ -57,14 +60,21 @@ pub fn run_debug(target: String) -> Result<(), Error> {
 
     write_debug_target(debug_dir.clone(), target)?;
 
-    let debug_bin = Command::new("cargo")
-        .args(&[
+    let mut build_cmd = Command::new("cargo");
+    build_cmd
+        .args([
             //"+nightly",
             "build",
             "--bin",
             &format!("debug_{}", target.name()),
+            "--target-dir",
         ])
-        .current_dir(&debug_dir)
+        .arg(target_dir)
+        .current_dir(&debug_dir);
+    if let Some(flags) = rust_flags {
+        build_cmd.env("RUSTFLAGS", flags);
+    }
+    let debug_bin = build_cmd
         .spawn()
         .context(format!("error starting {}", target.name()))?
         .wait()
@@ -77,7 +87,9 @@ pub fn run_debug(target: String) -> Result<(), Error> {
         "[WARF] Debug: {} compiled",
         &format!("debug_{}", target.name())
     );
-    Ok(())
+    Ok(target_dir
+        .join("debug")
+        .join(format!("debug_{}", target.name())))
 }
 
 pub fn write_debug_target(debug_dir: PathBuf, target: Targets) -> Result<(), Error> {
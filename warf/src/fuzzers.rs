@@ -1,23 +1,188 @@
 use failure::{Error, ResultExt};
+use std::env;
 use std::fs;
 use std::io::Write;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
 use structopt::StructOpt;
 
-use crate::targets::Targets;
+use crate::rust_fuzzers::{FuzzerAfl, FuzzerHfuzz, FuzzerLibfuzzer};
+use crate::stats::{record_stats, Stats};
+use crate::targets::{target_corpus_dir, Targets};
+use crate::utils::sync_inputs;
 #[derive(Fail, Debug)]
 #[fail(display = "[WARF] Fuzzer quit")]
 pub struct FuzzerQuit;
 
-arg_enum! {
-    #[derive(StructOpt, Debug, Clone, Copy, PartialEq, Eq)]
-    pub enum Fuzzer {
-        Afl,
-        Honggfuzz,
-        Libfuzzer
+/// Held while a target is prepared and built: targets of the same fuzzer
+/// share its workspace, even when several of them are fuzzed at once.
+static BUILD_LOCK: Mutex<()> = Mutex::new(());
+
+/// Delay between two summaries of a running fuzzer
+const STATS_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Fuzzing engine able to run the fuzz targets.
+pub trait Fuzzer: Sync {
+    /// Name of the fuzzer
+    fn name(&self) -> &str;
+    /// Check if the fuzzer is installed
+    fn is_available(&self) -> Result<(), Error>;
+    /// Create the fuzzer workspace and write the fuzz target
+    fn prepare(&self, target: Targets) -> Result<(), Error>;
+    /// Build the fuzz target
+    fn build(&self, target: Targets) -> Result<(), Error>;
+    /// Run the fuzzer on a target already built
+    fn run(&self, target: Targets) -> Result<(), Error>;
+    /// Directory where the fuzzer saves the crashing inputs of the target
+    fn crashes_dir(&self, target: Targets) -> Result<PathBuf, Error>;
+    /// Directory where the fuzzer saves the corpus of the target
+    fn corpus_dir(&self, target: Targets) -> Result<PathBuf, Error>;
+    /// Statistics of the current (or last) session of the fuzzer on the target
+    fn stats(&self, target: Targets) -> Result<Stats, Error>;
+
+    /// Minimize the corpus `input` of the target into `output`, keeping
+    /// only the inputs needed to reach the same coverage
+    fn cmin(&self, target: Targets, input: &Path, output: &Path) -> Result<(), Error>;
+
+    /// Minimize a crashing input of the target into `output`
+    fn minimize(&self, _target: Targets, _crash: &Path, _output: &Path) -> Result<(), Error> {
+        bail!("crash minimization not supported by this fuzzer")
+    }
+
+    /// Copy the new inputs found by the fuzzer into the corpus of the
+    /// target, the one used by all the fuzzers
+    fn sync_corpus(&self, target: Targets) -> Result<(), Error> {
+        let corpus_dir = self.corpus_dir(target)?;
+        let shared_dir = target_corpus_dir(target)?;
+        if corpus_dir == shared_dir || !corpus_dir.is_dir() {
+            return Ok(());
+        }
+        let synced = sync_inputs(&corpus_dir, &shared_dir)?;
+        if synced > 0 {
+            println!(
+                "[WARF] {}: {} new inputs synced into {}",
+                target.name(),
+                synced,
+                shared_dir.display()
+            );
+        }
+        Ok(())
+    }
+
+    /// Prepare, build then run the fuzzer on the target
+    fn fuzz(&self, target: Targets) -> Result<(), Error> {
+        {
+            let _lock = BUILD_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+            self.prepare(target)?;
+            self.build(target)?;
+        }
+        let start = Instant::now();
+        let done = AtomicBool::new(false);
+        let result = thread::scope(|s| {
+            // Summary of the session while the fuzzer is running
+            s.spawn(|| {
+                let mut last = Instant::now();
+                while !done.load(Ordering::SeqCst) {
+                    thread::sleep(Duration::from_millis(100));
+                    if last.elapsed() < STATS_INTERVAL {
+                        continue;
+                    }
+                    last = Instant::now();
+                    if let Ok(mut stats) = self.stats(target) {
+                        stats.elapsed = start.elapsed().as_secs();
+                        println!("{}", stats.summary(&target.name(), self.name()));
+                    }
+                }
+            });
+            let result = self.run(target);
+            done.store(true, Ordering::SeqCst);
+            result
+        });
+
+        // The session is saved even if the fuzzer failed, the errors
+        // below are only reported so that the one of the fuzzer is kept
+        let recorded = self.stats(target).and_then(|mut stats| {
+            stats.elapsed = start.elapsed().as_secs();
+            println!("{}", stats.summary(&target.name(), self.name()));
+            record_stats(target, self.name(), &stats)
+        });
+        if let Err(e) = recorded {
+            println!(
+                "[WARF] {}: unable to record statistics: {}",
+                target.name(),
+                e
+            );
+        }
+
+        // Let the next fuzzers build on the progress of this session
+        if let Err(e) = self.sync_corpus(target) {
+            println!("[WARF] {}: unable to sync corpus: {}", target.name(), e);
+        }
+
+        // Tell where to find the crashes, if any
+        if let Ok(crashes_dir) = self.crashes_dir(target) {
+            if let Some(Ok(_)) = fs::read_dir(&crashes_dir)
+                .ok()
+                .and_then(|mut dir| dir.next())
+            {
+                println!(
+                    "[WARF] {}: crashes saved in {}",
+                    target.name(),
+                    crashes_dir.display()
+                );
+            }
+        }
+        result
     }
 }
 
+/// Create a fuzzer, without checking that it is installed.
+type NewFuzzer = fn(FuzzerConfig) -> Result<Box<dyn Fuzzer>, Error>;
+
+fn new_fuzzer<F: Fuzzer + 'static>(
+    new: fn(FuzzerConfig) -> Result<F, Error>,
+    config: FuzzerConfig,
+) -> Result<Box<dyn Fuzzer>, Error> {
+    Ok(Box::new(new(config)?))
+}
+
+/// All the fuzzers available with the `--fuzzer` option.
+const FUZZERS: &[(&str, NewFuzzer)] = &[
+    ("Afl", |config| new_fuzzer(FuzzerAfl::new, config)),
+    ("Honggfuzz", |config| new_fuzzer(FuzzerHfuzz::new, config)),
+    ("Libfuzzer", |config| {
+        new_fuzzer(FuzzerLibfuzzer::new, config)
+    }),
+];
+
+/// Names of all the fuzzers
+pub fn get_fuzzers() -> Vec<&'static str> {
+    FUZZERS.iter().map(|(name, _)| *name).collect()
+}
+
+/// Create the fuzzer called `name` (case insensitive), checking
+/// that it is installed
+pub fn get_fuzzer(name: &str, config: FuzzerConfig) -> Result<Box<dyn Fuzzer>, Error> {
+    let fuzzer = match FUZZERS
+        .iter()
+        .find(|(fuzzer, _)| fuzzer.eq_ignore_ascii_case(name))
+    {
+        Some((_, new)) => new(config)?,
+        None => bail!("Don't know fuzzer `{}`", name),
+    };
+    fuzzer.is_available()?;
+    Ok(fuzzer)
+}
+
+/// Create all the fuzzers, installed or not, e.g. to collect
+/// the results of previous fuzzing sessions
+pub fn all_fuzzers(config: FuzzerConfig) -> Result<Vec<Box<dyn Fuzzer>>, Error> {
+    FUZZERS.iter().map(|(_, new)| new(config.clone())).collect()
+}
+
 arg_enum! {
     /// All the Sanitizers currently available
     ///
@@ -56,6 +221,35 @@ pub struct FuzzerConfig {
     pub seed: Option<i32>,
     // Dictionary
     pub dict: Option<String>,
+    // Fuel of execution targets
+    pub fuel: Option<u64>,
+    // Timeout of one execution, in seconds
+    pub exec_timeout: Option<u64>,
+}
+
+impl FuzzerConfig {
+    /// Environment variables read by the fuzzing harnesses.
+    pub fn harness_envs(&self) -> Vec<(String, String)> {
+        let mut envs = Vec::new();
+        if let Some(fuel) = self.fuel {
+            envs.push(("WARF_FUEL".to_string(), fuel.to_string()));
+        }
+        envs
+    }
+
+    /// RUSTFLAGS used to build the fuzz targets, with the sanitizer.
+    pub fn rust_flags(&self) -> String {
+        format!(
+            "{} \
+            {}",
+            if let Some(san) = self.sanitizer {
+                format!("-Z sanitizer={}", san.name())
+            } else {
+                "".into()
+            },
+            env::var("RUSTFLAGS").unwrap_or_default()
+        )
+    }
 }
 
 /// Write the fuzzing target
@@ -10,17 +10,25 @@ extern crate strum;
 #[macro_use]
 extern crate strum_macros;
 
-use crate::strum::IntoEnumIterator;
 use failure::Error;
 use structopt::StructOpt;
 
+// Declared first, its macros are used by the other modules
+#[macro_use]
+mod utils;
+
+mod cmin;
+mod coverage;
 mod debug;
 mod env;
 mod exec_all;
 mod fuzzers;
+mod minimize;
 mod rust_fuzzers;
+mod stats;
 mod targets;
-mod utils;
+mod triage;
+mod trophies;
 
 /// WARF - WebAssembly Runtimes Fuzzing project
 #[derive(StructOpt, Debug)]
@@ -36,12 +44,9 @@ enum Cli {
             short = "f",
             long = "fuzzer",
             default_value = "Honggfuzz",
-            raw(
-                possible_values = "&fuzzers::Fuzzer::variants()",
-                case_insensitive = "true"
-            )
+            raw(possible_values = "&fuzzers::get_fuzzers()", case_insensitive = "true")
         )]
-        fuzzer: fuzzers::Fuzzer,
+        fuzzer: String,
         /// Set timeout per target
         #[structopt(short = "t", long = "timeout", default_value = "10")]
         timeout: i32,
@@ -60,9 +65,18 @@ enum Cli {
             )
         )]
         sanitizer: Option<fuzzers::Sanitizer>,
+        /// Set the fuel of execution targets (default: 100000)
+        #[structopt(long = "fuel")]
+        fuel: Option<u64>,
+        /// Set the timeout of one execution in seconds (default: 2 for hfuzz)
+        #[structopt(long = "exec-timeout")]
+        exec_timeout: Option<u64>,
         // Run until the end of time (or Ctrl+C)
         #[structopt(short = "i", long = "infinite")]
         infinite: bool,
+        /// Number of targets fuzzed at the same time, sharing the threads
+        #[structopt(short = "j", long = "jobs", default_value = "1")]
+        jobs: usize,
     },
     /// Run one target with specific fuzzer
     #[structopt(name = "target")]
@@ -74,12 +88,9 @@ enum Cli {
             short = "f",
             long = "fuzzer",
             default_value = "Honggfuzz",
-            raw(
-                possible_values = "&fuzzers::Fuzzer::variants()",
-                case_insensitive = "true"
-            )
+            raw(possible_values = "&fuzzers::get_fuzzers()", case_insensitive = "true")
         )]
-        fuzzer: fuzzers::Fuzzer,
+        fuzzer: String,
         /// Set timeout
         #[structopt(short = "t", long = "timeout")]
         timeout: Option<i32>,
@@ -101,6 +112,12 @@ enum Cli {
             )
         )]
         sanitizer: Option<fuzzers::Sanitizer>,
+        /// Set the fuel of execution targets (default: 100000)
+        #[structopt(long = "fuel")]
+        fuel: Option<u64>,
+        /// Set the timeout of one execution in seconds (default: 2 for hfuzz)
+        #[structopt(long = "exec-timeout")]
+        exec_timeout: Option<u64>,
     },
     /// Debug one target
     #[structopt(name = "debug")]
@@ -108,9 +125,65 @@ enum Cli {
         /// Which target to debug
         target: String,
     },
+    /// Coverage report of the corpus of one target
+    #[structopt(name = "coverage")]
+    Coverage {
+        /// Which target to measure the coverage of
+        target: String,
+    },
     /// List all available targets
     #[structopt(name = "list")]
     ListTargets,
+    /// Minimize a crash of one target
+    #[structopt(name = "minimize")]
+    Minimize {
+        /// Which target crashed
+        target: String,
+        /// Crashing input to minimize
+        crash: String,
+        /// Which fuzzer to use (default: first one installed)
+        #[structopt(
+            short = "f",
+            long = "fuzzer",
+            raw(possible_values = "&fuzzers::get_fuzzers()", case_insensitive = "true")
+        )]
+        fuzzer: Option<String>,
+    },
+    /// Minimize the corpus of one target
+    #[structopt(name = "cmin")]
+    Cmin {
+        /// Which target to minimize the corpus of
+        target: String,
+        /// Which fuzzer to use
+        #[structopt(
+            short = "f",
+            long = "fuzzer",
+            default_value = "Honggfuzz",
+            raw(possible_values = "&fuzzers::get_fuzzers()", case_insensitive = "true")
+        )]
+        fuzzer: String,
+        /// Minimize the corpora shared by the targets of the same type
+        /// instead of the corpus of the target
+        #[structopt(long = "in-place")]
+        in_place: bool,
+    },
+    /// Gather and deduplicate the crashes of all fuzzers
+    #[structopt(name = "triage")]
+    Triage {
+        /// Only triage target containing this string
+        #[structopt(short = "q", long = "filter")]
+        filter: Option<String>,
+    },
+    /// Replay the trophies on all targets
+    #[structopt(name = "trophies")]
+    Trophies {
+        /// Directory of the trophies
+        #[structopt(short = "d", long = "dir", default_value = "../trophies")]
+        dir: String,
+        /// Only replay on target containing this string
+        #[structopt(short = "q", long = "filter")]
+        filter: Option<String>,
+    },
     /// Run WebAssembly module on all targets
     #[structopt(name = "execute-all")]
     ExecuteAll {
@@ -161,6 +234,8 @@ fn run() -> Result<(), Error> {
             seed,
             dict,
             sanitizer,
+            fuel,
+            exec_timeout,
         } => {
             let config = fuzzers::FuzzerConfig {
                 timeout,
@@ -168,6 +243,8 @@ fn run() -> Result<(), Error> {
                 sanitizer,
                 seed,
                 dict,
+                fuel,
+                exec_timeout,
             };
             run_target(target, fuzzer, config)?;
         }
@@ -175,6 +252,34 @@ fn run() -> Result<(), Error> {
         Debug { target } => {
             debug::run_debug(target)?;
         }
+        // Coverage of the corpus of one target
+        Coverage { target } => {
+            coverage::run_coverage(target)?;
+        }
+        // Minimize one crash
+        Minimize {
+            target,
+            crash,
+            fuzzer,
+        } => {
+            minimize::run_minimize(target, crash, fuzzer)?;
+        }
+        // Minimize the corpus of one target
+        Cmin {
+            target,
+            fuzzer,
+            in_place,
+        } => {
+            cmin::run_cmin(target, fuzzer, in_place)?;
+        }
+        // Gather the crashes of all fuzzers
+        Triage { filter } => {
+            triage::run_triage(filter)?;
+        }
+        // Replay the trophies on all targets
+        Trophies { dir, filter } => {
+            trophies::run_trophies(dir, filter)?;
+        }
         // Fuzz multiple targets
         Continuous {
             filter,
@@ -183,7 +288,10 @@ fn run() -> Result<(), Error> {
             thread,
             seed,
             sanitizer,
+            fuel,
+            exec_timeout,
             infinite,
+            jobs,
         } => {
             let config = fuzzers::FuzzerConfig {
                 timeout: Some(timeout),
@@ -191,8 +299,10 @@ fn run() -> Result<(), Error> {
                 sanitizer,
                 seed,
                 dict: None,
+                fuel,
+                exec_timeout,
             };
-            run_continuously(filter, fuzzer, config, infinite)?;
+            run_continuously(filter, fuzzer, config, infinite, jobs)?;
         }
     }
     Ok(())
@@ -207,96 +317,94 @@ fn list_targets() -> Result<(), Error> {
 }
 
 /// Run fuzzing on only one target
-fn run_target(
-    target: String,
-    fuzzer: fuzzers::Fuzzer,
-    config: fuzzers::FuzzerConfig,
-) -> Result<(), Error> {
-    let target = match targets::Targets::iter().find(|x| x.name() == target) {
-        None => bail!(
-            "Don't know target `{}`. {}",
-            target,
-            if let Some(alt) = utils::did_you_mean(&target, &targets::get_targets()) {
-                format!("Did you mean `{}`?", alt)
-            } else {
-                "".into()
-            }
-        ),
-        Some(t) => t,
-    };
+fn run_target(target: String, fuzzer: String, config: fuzzers::FuzzerConfig) -> Result<(), Error> {
+    let target = targets::find_target(&target)?;
 
-    use fuzzers::Fuzzer::*;
-    match fuzzer {
-        Afl => {
-            let afl = rust_fuzzers::FuzzerAfl::new(config)?;
-            afl.run(target)?;
-        }
-        Honggfuzz => {
-            let hfuzz = rust_fuzzers::FuzzerHfuzz::new(config)?;
-            hfuzz.run(target)?;
-        }
-        Libfuzzer => {
-            let lfuzz = rust_fuzzers::FuzzerLibfuzzer::new(config)?;
-            lfuzz.run(target)?;
-        }
-    }
+    let fuzzer = fuzzers::get_fuzzer(&fuzzer, config)?;
+    fuzzer.fuzz(target)?;
     Ok(())
 }
 
-/// Run fuzzing on multiple target matching the filter option
+/// Run fuzzing on multiple target matching the filter option,
+/// `jobs` targets at a time
 fn run_continuously(
     filter: Option<String>,
-    fuzzer: fuzzers::Fuzzer,
-    config: fuzzers::FuzzerConfig,
+    fuzzer: String,
+    mut config: fuzzers::FuzzerConfig,
     infinite: bool,
+    jobs: usize,
 ) -> Result<(), Error> {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Mutex;
+    use std::thread;
+
+    if jobs == 0 {
+        bail!("at least one job is needed");
+    }
+    // Share the threads (all the cpus by default) between the jobs
+    if jobs > 1 {
+        let cpus = thread::available_parallelism()
+            .map(|n| n.get() as i32)
+            .unwrap_or(1);
+        let threads = config.thread.unwrap_or(cpus);
+        config.thread = Some((threads / jobs as i32).max(1));
+    }
+
     let run = |target: &str| -> Result<(), Error> {
-        let target = match targets::Targets::iter().find(|x| x.name() == target) {
-            None => bail!(
-                "Don't know target `{}`. {}",
-                target,
-                if let Some(alt) = utils::did_you_mean(&target, &targets::get_targets()) {
-                    format!("Did you mean `{}`?", alt)
-                } else {
-                    "".into()
-                }
-            ),
-            Some(t) => t,
-        };
+        let target = targets::find_target(target)?;
 
-        use fuzzers::Fuzzer::*;
-        match fuzzer {
-            Afl => {
-                let hfuzz = rust_fuzzers::FuzzerAfl::new(config.clone())?;
-                hfuzz.run(target)?;
-            }
-            Honggfuzz => {
-                let hfuzz = rust_fuzzers::FuzzerHfuzz::new(config.clone())?;
-                hfuzz.run(target)?;
-            }
-            Libfuzzer => {
-                let hfuzz = rust_fuzzers::FuzzerLibfuzzer::new(config.clone())?;
-                hfuzz.run(target)?;
+        let fuzzer = fuzzers::get_fuzzer(&fuzzer, config.clone())?;
+        fuzzer.fuzz(target)?;
+        Ok(())
+    };
+    // A failure of the fuzzer only skips the target
+    let run_or_skip = |target: &str| -> Result<(), Error> {
+        if let Err(e) = run(target) {
+            match e.downcast::<fuzzers::FuzzerQuit>() {
+                Ok(_) => println!("Fuzzer failed so we'll continue with the next one"),
+                Err(other_error) => return Err(other_error),
             }
         }
         Ok(())
     };
 
     let targets = targets::get_targets();
-    let targets = targets
+    let targets: Vec<&String> = targets
         .iter()
-        .filter(|x| filter.as_ref().map(|f| x.contains(f)).unwrap_or(true));
+        .filter(|x| filter.as_ref().map(|f| x.contains(f)).unwrap_or(true))
+        .collect();
 
     'cycle: loop {
-        'targets_pass: for target in targets.clone() {
-            if let Err(e) = run(target) {
-                match e.downcast::<fuzzers::FuzzerQuit>() {
-                    Ok(_) => {
-                        println!("Fuzzer failed so we'll continue with the next one");
-                        continue 'targets_pass;
+        // Each job takes the next target of the pass, in order
+        let next = AtomicUsize::new(0);
+        let error: Mutex<Option<Error>> = Mutex::new(None);
+        thread::scope(|s| {
+            for _ in 0..jobs {
+                s.spawn(|| {
+                    while error.lock().unwrap().is_none() {
+                        let target = match targets.get(next.fetch_add(1, Ordering::SeqCst)) {
+                            Some(target) => target,
+                            None => break,
+                        };
+                        if let Err(e) = run_or_skip(target) {
+                            *error.lock().unwrap() = Some(e);
+                        }
                     }
-                    Err(other_error) => return Err(other_error),
-                }
+                });
+            }
+        });
+        if let Some(e) = error.into_inner().unwrap() {
+            return Err(e);
+        }
+
+        // Overview of the pass, from the statistics recorded by the fuzzers
+        println!("[WARF] Summary of the targets:");
+        for target in &targets {
+            if let Some(summary) = targets::find_target(target)
+                .ok()
+                .and_then(stats::last_summary)
+            {
+                println!("{}", summary);
             }
         }
 
@@ -0,0 +1,62 @@
+use failure::{Error, ResultExt};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::debug::build_debug_target;
+use crate::fuzzers::{all_fuzzers, get_fuzzer, Fuzzer, FuzzerConfig};
+use crate::targets::find_target;
+use crate::triage::replay;
+
+/// Path of the minimized input, next to the crash
+fn minimized_path(crash: &Path) -> PathBuf {
+    let mut name = crash.file_name().unwrap_or_default().to_os_string();
+    name.push(".min");
+    crash.with_file_name(name)
+}
+
+/// Minimize a crash of the target with the given fuzzer, or with the
+/// first installed fuzzer able to do it.
+///
+/// The minimized input is only kept if it has the same crash
+/// signature than the original one.
+pub fn run_minimize(target: String, crash: String, fuzzer: Option<String>) -> Result<(), Error> {
+    let target = find_target(&target)?;
+    let crash = fs::canonicalize(&crash).context(format!("error reading crash {}", crash))?;
+    let output = minimized_path(&crash);
+
+    let debug_bin = build_debug_target(target)?;
+    let signature = match replay(&debug_bin, &crash)? {
+        Some((signature, _)) => signature,
+        None => bail!("{} doesn't crash {}", crash.display(), target.name()),
+    };
+
+    let fuzzers: Vec<Box<dyn Fuzzer>> = match fuzzer {
+        Some(name) => vec![get_fuzzer(&name, FuzzerConfig::default())?],
+        None => all_fuzzers(FuzzerConfig::default())?
+            .into_iter()
+            .filter(|fuzzer| fuzzer.is_available().is_ok())
+            .collect(),
+    };
+    for fuzzer in fuzzers {
+        if let Err(e) = fuzzer.minimize(target, &crash, &output) {
+            println!("[WARF] Minimize: {}", e);
+            continue;
+        }
+        match replay(&debug_bin, &output)? {
+            Some((min_signature, _)) if min_signature == signature => {
+                println!(
+                    "[WARF] Minimize: {} bytes -> {} bytes, saved in {}",
+                    fs::metadata(&crash)?.len(),
+                    fs::metadata(&output)?.len(),
+                    output.display()
+                );
+                return Ok(());
+            }
+            _ => {
+                println!("[WARF] Minimize: minimized input doesn't trigger the same crash");
+                fs::remove_file(&output)?;
+            }
+        }
+    }
+    bail!("unable to minimize {}", crash.display())
+}
@@ -2,14 +2,17 @@ use failure::{Error, ResultExt};
 use std::env;
 use std::ffi::OsStr;
 use std::fs;
-use std::path::PathBuf;
-use std::process::Command;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
 use strum::IntoEnumIterator;
 
-use crate::env::{corpora_dir, root_dir};
-use crate::fuzzers::{write_fuzzer_target, FuzzerConfig, FuzzerQuit};
-use crate::targets::{prepare_targets_workspace, Targets};
-use crate::utils::copy_dir;
+use crate::env::root_dir;
+use crate::fuzzers::{write_fuzzer_target, Fuzzer, FuzzerConfig, FuzzerQuit};
+use crate::stats::{count_files, parse_afl_stats, parse_hfuzz_log, parse_libfuzzer_session, Stats};
+use crate::targets::{prepare_targets_workspace, target_corpus_dir, Targets};
+use crate::utils::{copy_dir, host_triple, sync_inputs};
 
 /***********************************************
 name: honggfuzz-rs
@@ -30,20 +33,8 @@ pub struct FuzzerHfuzz {
 }
 
 impl FuzzerHfuzz {
-    /// Check if `cargo hfuzz` is installed
-    pub fn is_available() -> Result<(), Error> {
-        let fuzzer_output = Command::new("cargo").arg("hfuzz").arg("version").output()?;
-        if !fuzzer_output.status.success() {
-            bail!("hfuzz not available, install with `cargo install honggfuzz`");
-        }
-        Ok(())
-    }
-
     /// Create a new FuzzerHfuzz
     pub fn new(config: FuzzerConfig) -> Result<FuzzerHfuzz, Error> {
-        // Test if fuzzer engine installed
-        FuzzerHfuzz::is_available()?;
-
         let cwd = env::current_dir().context("error getting current directory")?;
         let fuzzer = FuzzerHfuzz {
             name: "Honggfuzz".to_string(),
@@ -55,72 +46,101 @@ impl FuzzerHfuzz {
         Ok(fuzzer)
     }
 
-    // TODO - simplify this function
-    fn prepare_fuzzer_workspace(&self) -> Result<(), Error> {
-        let hfuzz_dir = &self.work_dir;
-        fs::create_dir_all(&hfuzz_dir)
-            .context(format!("unable to create {} dir", hfuzz_dir.display()))?;
+    /// Logs of the last session on the target, read for its statistics
+    fn log_path(&self, target: Targets) -> PathBuf {
+        self.workspace_dir.join(format!("{}.log", target.name()))
+    }
+}
 
-        let src_dir = hfuzz_dir.join("src");
-        fs::create_dir_all(&src_dir)
-            .context(format!("unable to create {} dir", src_dir.display()))?;
+impl Fuzzer for FuzzerHfuzz {
+    fn name(&self) -> &str {
+        &self.name
+    }
 
-        fs::copy(self.dir.join("Cargo.toml"), hfuzz_dir.join("Cargo.toml"))?;
-        fs::copy(self.dir.join("template.rs"), hfuzz_dir.join("template.rs"))?;
-        fs::copy(self.dir.join("src").join("lib.rs"), src_dir.join("lib.rs"))?;
+    /// Check if `cargo hfuzz` is installed
+    fn is_available(&self) -> Result<(), Error> {
+        let fuzzer_output = Command::new("cargo").arg("hfuzz").arg("version").output()?;
+        if !fuzzer_output.status.success() {
+            bail!("hfuzz not available, install with `cargo install honggfuzz`");
+        }
         Ok(())
     }
 
-    pub fn run(&self, target: Targets) -> Result<(), Error> {
+    fn prepare(&self, target: Targets) -> Result<(), Error> {
         // check if target is supported by this fuzzer
         // TODO - change to make it automatic
         if target.language() != "rust" {
             bail!("FuzzerHfuzz incompatible for this target");
         }
 
-        // get path to corpora
-        let corpora_dir = corpora_dir()?.join(target.corpora());
-
         // copy targets folder into workspace
         prepare_targets_workspace()?;
 
         // create hfuzz folder inside workspace/
-        self.prepare_fuzzer_workspace()?;
+        prepare_fuzzer_workspace(&self.dir, &self.work_dir)?;
 
         // write all fuzz targets inside hfuzz folder
         write_fuzzer_target(&self.dir, &self.work_dir, target)?;
         println!("[WARF] {}: {} created", self.name, target.name());
+        Ok(())
+    }
 
-        // sanitizers
-        let rust_args = format!(
-            "{} \
-            {}",
-            if let Some(san) = self.config.sanitizer {
-                format!("-Z sanitizer={}", san.name())
-            } else {
-                "".into()
-            },
-            env::var("RUSTFLAGS").unwrap_or_default()
-        );
+    fn build(&self, target: Targets) -> Result<(), Error> {
+        let build_cmd = Command::new("cargo")
+            .args(&["+nightly", "hfuzz", "build"])
+            .env("RUSTFLAGS", self.config.rust_flags())
+            .current_dir(&self.work_dir)
+            .spawn()
+            .context(format!(
+                "error starting build for {} of {}",
+                self.name,
+                target.name()
+            ))?
+            .wait()
+            .context(format!(
+                "error while waiting for build for {} of {}",
+                self.name,
+                target.name()
+            ))?;
+
+        if !build_cmd.success() {
+            return Err(FuzzerQuit.into());
+        }
+        Ok(())
+    }
+
+    fn run(&self, target: Targets) -> Result<(), Error> {
+        // get path to corpora
+        let corpora_dir = self.corpus_dir(target)?;
 
         // Handle seed option
         if self.config.seed != None {
             println!("[WARF] {}: seed not supported", self.name);
         }
 
+        // logs of the previous session would give wrong statistics
+        let log_path = self.log_path(target);
+        fs::create_dir_all(&self.workspace_dir).context(format!(
+            "unable to create {} dir",
+            self.workspace_dir.display()
+        ))?;
+        let _ = fs::remove_file(&log_path);
+
         // prepare arguments
         let hfuzz_args = format!(
             "{} \
+             -t {} \
              {} \
              {} \
-             {} \
+             --logfile {} \
              {}",
             if let Some(t) = self.config.timeout {
                 format!("--run_time {}", t)
             } else {
                 "".into()
             },
-            "-t 2",
+            // Timeout of one execution
+            self.config.exec_timeout.unwrap_or(2),
             // Set number of thread
             if let Some(n) = self.config.thread {
                 format!("--threads {}", n)
@@ -133,16 +153,28 @@ impl FuzzerHfuzz {
             } else {
                 "".into()
             },
+            log_path.display(),
             env::var("HFUZZ_RUN_ARGS").unwrap_or_default()
         );
 
-        // Honggfuzz will first build than run the fuzzer using cargo
-        let fuzzer_bin = Command::new("cargo") // ,
-            .args(&["+nightly", "hfuzz", "run", &target.name()])
-            .env("RUSTFLAGS", &rust_args)
-            .env("HFUZZ_RUN_ARGS", &hfuzz_args)
-            //.env("HFUZZ_BUILD_ARGS", "opt-level=3")
-            .env("HFUZZ_INPUT", corpora_dir)
+        // Run the binaries of `build` like `cargo hfuzz run`, that would
+        // build them again outside of the build lock while other jobs
+        // prepare the same workspace
+        let hfuzz_target = self.work_dir.join("hfuzz_target");
+        let fuzzer_bin = Command::new(hfuzz_target.join("honggfuzz"))
+            .arg("--input")
+            .arg(&corpora_dir)
+            .arg("--workspace")
+            .arg(self.crashes_dir(target)?)
+            .args(hfuzz_args.split_whitespace())
+            .arg("--")
+            .arg(
+                hfuzz_target
+                    .join(host_triple()?)
+                    .join("release")
+                    .join(target.name()),
+            )
+            .envs(self.config.harness_envs())
             .current_dir(&self.work_dir)
             .spawn()
             .context(format!(
@@ -162,6 +194,75 @@ impl FuzzerHfuzz {
         }
         Ok(())
     }
+
+    fn crashes_dir(&self, target: Targets) -> Result<PathBuf, Error> {
+        Ok(self.workspace_dir.join(target.name()))
+    }
+
+    /// Honggfuzz adds new inputs into the corpora of the target
+    fn corpus_dir(&self, target: Targets) -> Result<PathBuf, Error> {
+        target_corpus_dir(target)
+    }
+
+    fn stats(&self, target: Targets) -> Result<Stats, Error> {
+        let log = fs::read_to_string(self.log_path(target)).unwrap_or_default();
+        Ok(Stats {
+            corpus_size: count_files(&self.corpus_dir(target)?),
+            ..parse_hfuzz_log(&log)
+        })
+    }
+
+    /// Minimize the corpus with the minimize mode of honggfuzz (`-M`)
+    fn cmin(&self, target: Targets, input: &Path, output: &Path) -> Result<(), Error> {
+        self.prepare(target)?;
+        fs::create_dir_all(output).context(format!("unable to create {} dir", output.display()))?;
+
+        let cmin_cmd = Command::new("cargo")
+            .args(["+nightly", "hfuzz", "run", &target.name()])
+            .env("RUSTFLAGS", self.config.rust_flags())
+            .env(
+                "HFUZZ_RUN_ARGS",
+                format!("-M --output {}", output.display()),
+            )
+            .env("HFUZZ_INPUT", input)
+            .envs(self.config.harness_envs())
+            .current_dir(&self.work_dir)
+            .spawn()
+            .context(format!(
+                "error starting {} to minimize corpus of {}",
+                self.name,
+                target.name()
+            ))?
+            .wait()
+            .context(format!(
+                "error while waiting for {} minimizing corpus of {}",
+                self.name,
+                target.name()
+            ))?;
+
+        if !cmin_cmd.success() {
+            return Err(FuzzerQuit.into());
+        }
+        Ok(())
+    }
+
+    // NOTE: the minimize mode of honggfuzz (`-M`) only minimizes a corpus,
+    // not a crashing input
+}
+
+/// Copy the template of a fuzzer into its workspace, for fuzzers
+/// using a cargo project with one binary per target.
+// TODO - simplify this function
+fn prepare_fuzzer_workspace(dir: &Path, work_dir: &Path) -> Result<(), Error> {
+    fs::create_dir_all(work_dir).context(format!("unable to create {} dir", work_dir.display()))?;
+
+    let src_dir = work_dir.join("src");
+    fs::create_dir_all(&src_dir).context(format!("unable to create {} dir", src_dir.display()))?;
+
+    fs::copy(dir.join("Cargo.toml"), work_dir.join("Cargo.toml"))?;
+    fs::copy(dir.join("template.rs"), work_dir.join("template.rs"))?;
+    fs::copy(dir.join("src").join("lib.rs"), src_dir.join("lib.rs"))?;
+    Ok(())
 }
 
 /***********************************************
@@ -183,20 +284,8 @@ pub struct FuzzerAfl {
 }
 
 impl FuzzerAfl {
-    /// Check if `cargo afl` is installed
-    pub fn is_available() -> Result<(), Error> {
-        let fuzzer_output = Command::new("cargo").arg("afl").arg("--version").output()?;
-        if !fuzzer_output.status.success() {
-            bail!("afl-rs not available, install with `cargo install afl`");
-        }
-        Ok(())
-    }
-
     /// Create a new FuzzerAfl
     pub fn new(config: FuzzerConfig) -> Result<FuzzerAfl, Error> {
-        // Test if fuzzer engine installed
-        FuzzerAfl::is_available()?;
-
         let cwd = env::current_dir().context("error getting current directory")?;
         let fuzzer = FuzzerAfl {
             name: "Afl++".to_string(),
@@ -208,45 +297,47 @@ impl FuzzerAfl {
         Ok(fuzzer)
     }
 
-    // TODO - simplify that
-    fn prepare_fuzzer_workspace(&self) -> Result<(), Error> {
-        let hfuzz_dir = &self.work_dir;
-        fs::create_dir_all(&hfuzz_dir)
-            .context(format!("unable to create {} dir", hfuzz_dir.display()))?;
+    /// Output directory of afl for the target, one per target so that
+    /// several targets can be fuzzed at the same time
+    fn output_dir(&self, target: Targets) -> PathBuf {
+        self.workspace_dir.join(target.name())
+    }
+}
 
-        let src_dir = hfuzz_dir.join("src");
-        fs::create_dir_all(&src_dir)
-            .context(format!("unable to create {} dir", src_dir.display()))?;
+impl Fuzzer for FuzzerAfl {
+    fn name(&self) -> &str {
+        &self.name
+    }
 
-        fs::copy(self.dir.join("Cargo.toml"), hfuzz_dir.join("Cargo.toml"))?;
-        fs::copy(self.dir.join("template.rs"), hfuzz_dir.join("template.rs"))?;
-        fs::copy(self.dir.join("src").join("lib.rs"), src_dir.join("lib.rs"))?;
+    /// Check if `cargo afl` is installed
+    fn is_available(&self) -> Result<(), Error> {
+        let fuzzer_output = Command::new("cargo").arg("afl").arg("--version").output()?;
+        if !fuzzer_output.status.success() {
+            bail!("afl-rs not available, install with `cargo install afl`");
+        }
         Ok(())
     }
 
-    /// Build single target with afl
-    pub fn build_afl(&self, target: Targets) -> Result<(), Error> {
+    fn prepare(&self, target: Targets) -> Result<(), Error> {
+        // check if target is supported by this fuzzer
+        // TODO - change to make it automatic
+        if target.language() != "rust" {
+            bail!("FuzzerAfl incompatible for this target");
+        }
+
         prepare_targets_workspace()?;
         // create afl folder inside workspace/
-        self.prepare_fuzzer_workspace()?;
+        prepare_fuzzer_workspace(&self.dir, &self.work_dir)?;
 
         write_fuzzer_target(&self.dir, &self.work_dir, target)?;
+        Ok(())
+    }
 
-        // sanitizers
-        let rust_args = format!(
-            "{} \
-            {}",
-            if let Some(san) = self.config.sanitizer {
-                format!("-Z sanitizer={}", san.name())
-            } else {
-                "".into()
-            },
-            env::var("RUSTFLAGS").unwrap_or_default()
-        );
-
+    /// Build single target with afl
+    fn build(&self, target: Targets) -> Result<(), Error> {
         let build_cmd = Command::new("cargo") // "+nightly",
             .args(&["afl", "build", "--bin", &target.name()]) // TODO: not sure we want to compile afl in "--release"
-            .env("RUSTFLAGS", &rust_args)
+            .env("RUSTFLAGS", self.config.rust_flags())
             .current_dir(&self.work_dir)
             .spawn()
             .context(format!(
@@ -268,26 +359,25 @@ impl FuzzerAfl {
         Ok(())
     }
 
-    pub fn run(&self, target: Targets) -> Result<(), Error> {
-        // check if target is supported by this fuzzer
-        // TODO - change to make it automatic
-        if target.language() != "rust" {
-            bail!("FuzzerAfl incompatible for this target");
-        }
-
+    fn run(&self, target: Targets) -> Result<(), Error> {
         let dir = &self.work_dir;
-        let corpora_dir = corpora_dir()?.join(target.corpora());
+        let corpora_dir = target_corpus_dir(target)?;
 
-        self.build_afl(target)?;
-
-        // TODO - modify to use same corpus than other fuzzer
-        let corpus_dir = &self.workspace_dir;
+        // afl keeps its own queue, synced with the corpus of the target
+        // by `sync_corpus` at the end of the session
+        let corpus_dir = self.output_dir(target);
         fs::create_dir_all(&corpus_dir)
             .context(format!("unable to create {} dir", corpus_dir.display()))?;
+        // statistics of the previous session, rewritten once afl started
+        let _ = fs::remove_file(corpus_dir.join("fuzzer_stats"));
 
         // Determined if existing fuzzing session exist
-        let queue_dir = corpus_dir.join("queue");
-        let input_arg: &OsStr = if queue_dir.is_dir() && fs::read_dir(queue_dir)?.next().is_some() {
+        let queue_dir = self.corpus_dir(target)?;
+        let input_arg: &OsStr = if queue_dir.is_dir() && fs::read_dir(&queue_dir)?.next().is_some()
+        {
+            // afl reads all the files of the queue when resuming,
+            // including the inputs found since by the other fuzzers
+            sync_inputs(&corpora_dir, &queue_dir)?;
             "-".as_ref()
         } else {
             corpora_dir.as_ref()
@@ -322,6 +412,7 @@ impl FuzzerAfl {
             .env("AFL_SKIP_CPUFREQ", "1")
             .env("AFL_SKIP_CRASHES", "1")
             .env("AFL_I_DONT_CARE_ABOUT_MISSING_CRASHES", "1")
+            .envs(self.config.harness_envs())
             .current_dir(&dir)
             .spawn()
             .context(format!(
@@ -341,6 +432,76 @@ impl FuzzerAfl {
         }
         Ok(())
     }
+
+    fn crashes_dir(&self, target: Targets) -> Result<PathBuf, Error> {
+        Ok(self.output_dir(target).join("crashes"))
+    }
+
+    fn corpus_dir(&self, target: Targets) -> Result<PathBuf, Error> {
+        Ok(self.output_dir(target).join("queue"))
+    }
+
+    fn stats(&self, target: Targets) -> Result<Stats, Error> {
+        let fuzzer_stats =
+            fs::read_to_string(self.output_dir(target).join("fuzzer_stats")).unwrap_or_default();
+        Ok(parse_afl_stats(&fuzzer_stats))
+    }
+
+    /// Minimize the corpus with `afl-cmin`
+    ///
+    /// NOTE: afl-cmin refuses to write into a non empty directory
+    fn cmin(&self, target: Targets, input: &Path, output: &Path) -> Result<(), Error> {
+        self.prepare(target)?;
+        self.build(target)?;
+
+        let cmin_cmd = Command::new("cargo")
+            .args(["afl", "cmin", "-m", "none", "-i"])
+            .arg(input)
+            .arg("-o")
+            .arg(output)
+            .args(["--", &format!("./target/debug/{}", target.name())])
+            .envs(self.config.harness_envs())
+            .current_dir(&self.work_dir)
+            .spawn()
+            .context(format!("error starting afl-cmin for {}", target.name()))?
+            .wait()
+            .context(format!(
+                "error while waiting for afl-cmin for {}",
+                target.name()
+            ))?;
+
+        if !cmin_cmd.success() {
+            return Err(FuzzerQuit.into());
+        }
+        Ok(())
+    }
+
+    /// Minimize the crash with `afl-tmin`
+    fn minimize(&self, target: Targets, crash: &Path, output: &Path) -> Result<(), Error> {
+        self.prepare(target)?;
+        self.build(target)?;
+
+        let tmin_cmd = Command::new("cargo")
+            .args(["afl", "tmin", "-i"])
+            .arg(crash)
+            .arg("-o")
+            .arg(output)
+            .args(["--", &format!("./target/debug/{}", target.name())])
+            .envs(self.config.harness_envs())
+            .current_dir(&self.work_dir)
+            .spawn()
+            .context(format!("error starting afl-tmin for {}", target.name()))?
+            .wait()
+            .context(format!(
+                "error while waiting for afl-tmin for {}",
+                target.name()
+            ))?;
+
+        if !tmin_cmd.success() {
+            return Err(FuzzerQuit.into());
+        }
+        Ok(())
+    }
 }
 
 /***********************************************
@@ -348,6 +509,9 @@ name: libfuzzer/cargo-fuzz
 github: https://github.com/rust-fuzz/cargo-fuzz
 ***********************************************/
 
+/// Set once the fuzz targets of the libfuzzer workspace have been reset
+static WORKSPACE_RESET: AtomicBool = AtomicBool::new(false);
+
 pub struct FuzzerLibfuzzer {
     /// Fuzzer name.
     pub name: String,
@@ -355,52 +519,48 @@ pub struct FuzzerLibfuzzer {
     pub dir: PathBuf,
     /// Workspace dir
     pub work_dir: PathBuf,
-    /// Internal workspace dir
-    pub workspace_dir: PathBuf,
     /// fuzzing config
     pub config: FuzzerConfig,
 }
 
 impl FuzzerLibfuzzer {
-    /// Check if `cargo fuzz` is installed
-    pub fn is_available() -> Result<(), Error> {
-        let fuzzer_output = Command::new("cargo")
-            .arg("fuzz")
-            .arg("--version")
-            .output()?;
-        if !fuzzer_output.status.success() {
-            bail!("cargo-fuzz not available, install with `cargo install cargo-fuzz`");
-        }
-        Ok(())
-    }
-
     /// Create a new FuzzerLibfuzzer
     pub fn new(config: FuzzerConfig) -> Result<FuzzerLibfuzzer, Error> {
-        // Test if fuzzer engine installed
-        FuzzerLibfuzzer::is_available()?;
-
         let cwd = env::current_dir().context("error getting current directory")?;
         let fuzzer = FuzzerLibfuzzer {
             name: "Libfuzzer".to_string(),
             dir: cwd.join("fuzzers").join("rust-libfuzzer"),
             work_dir: cwd.join("workspace").join("libfuzzer"),
-            workspace_dir: cwd
-                .join("workspace")
-                .join("libfuzzer")
-                .join("libfuzzer_workspace"),
             config,
         };
         Ok(fuzzer)
     }
 
-    fn prepare_fuzzer_workspace(&self) -> Result<(), Error> {
-        let from = &self.dir;
-        let workspace = &self.work_dir;
-        copy_dir(from.to_path_buf(), workspace.to_path_buf())?;
+    /// Outputs of the last session on the target, read for its statistics:
+    /// `fuzz.log` and with `-jobs` the `fuzz-<n>.log` of every job
+    fn logs_dir(&self, target: Targets) -> PathBuf {
+        self.work_dir.join("fuzz").join("logs").join(target.name())
+    }
+}
+
+impl Fuzzer for FuzzerLibfuzzer {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Check if `cargo fuzz` is installed
+    fn is_available(&self) -> Result<(), Error> {
+        let fuzzer_output = Command::new("cargo")
+            .arg("fuzz")
+            .arg("--version")
+            .output()?;
+        if !fuzzer_output.status.success() {
+            bail!("cargo-fuzz not available, install with `cargo install cargo-fuzz`");
+        }
         Ok(())
     }
 
-    pub fn run(&self, target: Targets) -> Result<(), Error> {
+    fn prepare(&self, target: Targets) -> Result<(), Error> {
         // check if target is supported by this fuzzer
         // TODO - change to make it automatic
         if target.language() != "rust" {
@@ -408,13 +568,18 @@ impl FuzzerLibfuzzer {
         }
 
         prepare_targets_workspace()?;
-        // create afl folder inside workspace/
-        self.prepare_fuzzer_workspace()?;
+        // create libfuzzer folder inside workspace/
+        copy_dir(self.dir.to_path_buf(), self.work_dir.to_path_buf())?;
 
         let fuzz_dir = self.work_dir.join("fuzz");
         fs::create_dir_all(&fuzz_dir)
             .context(format!("unable to create {} dir", fuzz_dir.display()))?;
 
+        // The targets are reset once per run of warf, not while other
+        // jobs use the workspace
+        if WORKSPACE_RESET.load(Ordering::SeqCst) {
+            return Ok(());
+        }
         let target_dir = fuzz_dir.join("fuzz_targets");
 
         let _ = fs::remove_dir_all(&target_dir)
@@ -422,10 +587,6 @@ impl FuzzerLibfuzzer {
         fs::create_dir_all(&target_dir)
             .context(format!("unable to create {} dir", target_dir.display()))?;
 
-        fs::create_dir_all(&fuzz_dir)
-            .context(format!("unable to create {} dir", fuzz_dir.display()))?;
-        //println!("{:?}", fuzz_dir);
-
         fs::copy(
             self.dir.join("fuzz").join("Cargo.toml"),
             fuzz_dir.join("Cargo.toml"),
@@ -435,31 +596,50 @@ impl FuzzerLibfuzzer {
         for target in Targets::iter().filter(|x| x.language() == "rust") {
             write_libfuzzer_target(&self.work_dir, target)?;
         }
+        WORKSPACE_RESET.store(true, Ordering::SeqCst);
+        Ok(())
+    }
 
-        let fuzz_dir = self.work_dir.join("fuzz");
-        let corpus_dir = corpora_dir()?.join(target.corpora());
+    fn build(&self, target: Targets) -> Result<(), Error> {
+        let build_cmd = Command::new("cargo") // "+nightly",
+            .args(&["fuzz", "build", &target.name()])
+            .env("RUSTFLAGS", self.config.rust_flags())
+            .current_dir(self.work_dir.join("fuzz"))
+            .spawn()
+            .context(format!(
+                "error starting build for {} of {}",
+                self.name,
+                target.name()
+            ))?
+            .wait()
+            .context(format!(
+                "error while waiting for build for {} of {}",
+                self.name,
+                target.name()
+            ))?;
 
-        // sanitizers
-        let rust_args = format!(
-            "{} \
-            {}",
-            if let Some(san) = self.config.sanitizer {
-                format!("-Z sanitizer={}", san.name())
-            } else {
-                "".into()
-            },
-            env::var("RUSTFLAGS").unwrap_or_default()
-        );
+        if !build_cmd.success() {
+            return Err(FuzzerQuit.into());
+        }
+        Ok(())
+    }
+
+    fn run(&self, target: Targets) -> Result<(), Error> {
+        let fuzz_dir = self.work_dir.join("fuzz");
+        let corpus_dir = self.corpus_dir(target)?;
 
         // create arguments
         // corpora dir
         // max_time if provided (i.e. continuously fuzzing)
         let mut args: Vec<String> = Vec::new();
         args.push(format!("{}", &corpus_dir.display()));
+        args.push("-print_final_stats=1".to_string());
         if let Some(timeout) = self.config.timeout {
-            args.push("--".to_string());
             args.push(format!("-max_total_time={}", timeout));
         };
+        if let Some(timeout) = self.config.exec_timeout {
+            args.push(format!("-timeout={}", timeout));
+        };
         // threading
         if let Some(thread) = self.config.thread {
             args.push(format!("-workers={}", thread));
@@ -473,30 +653,184 @@ impl FuzzerLibfuzzer {
         if let Some(dict) = self.config.dict.clone() {
             args.push(format!("-dict={}", root_dir()?.join(dict).display()));
         };
-        // Launch the fuzzer using cargo
-        let fuzzer_bin = Command::new("cargo") // "+nightly",
-            .args(&["fuzz", "run", &target.name()])
+        // libFuzzer reports its progress on stderr, also saved for the statistics
+        // NOTE: with `-jobs`, the output of every job goes into its own
+        // `fuzz-<n>.log` inside the current directory
+        let logs_dir = self.logs_dir(target);
+        if logs_dir.exists() {
+            fs::remove_dir_all(&logs_dir)
+                .context(format!("error removing {}", logs_dir.display()))?;
+        }
+        fs::create_dir_all(&logs_dir)
+            .context(format!("unable to create {} dir", logs_dir.display()))?;
+        let log_path = logs_dir.join("fuzz.log");
+        let mut log = fs::File::create(&log_path)
+            .context(format!("error creating {}", log_path.display()))?;
+
+        // Run the binary of `build` like `cargo fuzz run`, that would
+        // build it again outside of the build lock while other jobs
+        // prepare the same workspace
+        let crashes_dir = self.crashes_dir(target)?;
+        fs::create_dir_all(&crashes_dir)
+            .context(format!("unable to create {} dir", crashes_dir.display()))?;
+        fs::create_dir_all(&corpus_dir)
+            .context(format!("unable to create {} dir", corpus_dir.display()))?;
+        let fuzz_bin = fuzz_dir
+            .join("target")
+            .join(host_triple()?)
+            .join("release")
+            .join(target.name());
+        let mut child = Command::new(&fuzz_bin)
+            .arg(format!("-artifact_prefix={}/", crashes_dir.display()))
             .args(&args)
-            .env("RUSTFLAGS", &rust_args)
-            .current_dir(&fuzz_dir)
+            .envs(self.config.harness_envs())
+            .current_dir(&logs_dir)
+            .stderr(Stdio::piped())
             .spawn()
             .context(format!(
                 "error starting {:?} to run {}",
                 self.name,
                 target.name()
+            ))?;
+        let tee = child.stderr.take().map(|mut stderr| {
+            thread::spawn(move || {
+                use std::io::{Read, Write};
+
+                let mut buffer = [0u8; 4096];
+                while let Ok(n) = stderr.read(&mut buffer) {
+                    if n == 0 {
+                        break;
+                    }
+                    let _ = std::io::stderr().write_all(&buffer[..n]);
+                    let _ = log.write_all(&buffer[..n]);
+                }
+            })
+        });
+        let fuzzer_bin = child.wait().context(format!(
+            "error while waiting for {:?} running {}",
+            self.name,
+            target.name()
+        ))?;
+        if let Some(tee) = tee {
+            let _ = tee.join();
+        }
+
+        if !fuzzer_bin.success() {
+            return Err(FuzzerQuit.into());
+        }
+        Ok(())
+    }
+
+    fn crashes_dir(&self, target: Targets) -> Result<PathBuf, Error> {
+        Ok(self
+            .work_dir
+            .join("fuzz")
+            .join("artifacts")
+            .join(target.name()))
+    }
+
+    /// libFuzzer adds new inputs into the corpora of the target
+    fn corpus_dir(&self, target: Targets) -> Result<PathBuf, Error> {
+        target_corpus_dir(target)
+    }
+
+    fn stats(&self, target: Targets) -> Result<Stats, Error> {
+        let logs_dir = self.logs_dir(target);
+        let log = fs::read_to_string(logs_dir.join("fuzz.log")).unwrap_or_default();
+        let mut job_logs = Vec::new();
+        if let Ok(entries) = fs::read_dir(&logs_dir) {
+            for entry in entries {
+                let path = entry?.path();
+                let name = path.file_name().unwrap_or_default().to_string_lossy();
+                if name.starts_with("fuzz-") && name.ends_with(".log") {
+                    job_logs.push(fs::read_to_string(&path).unwrap_or_default());
+                }
+            }
+        }
+        let mut stats = parse_libfuzzer_session(&log, &job_logs);
+        if stats.corpus_size == 0 {
+            stats.corpus_size = count_files(&self.corpus_dir(target)?);
+        }
+        Ok(stats)
+    }
+
+    /// Minimize the corpus with libFuzzer `-merge=1`, like `cargo fuzz cmin`
+    /// but without replacing the input corpus
+    fn cmin(&self, target: Targets, input: &Path, output: &Path) -> Result<(), Error> {
+        self.prepare(target)?;
+        fs::create_dir_all(output).context(format!("unable to create {} dir", output.display()))?;
+
+        let cmin_cmd = Command::new("cargo") // "+nightly",
+            .args(["fuzz", "run", &target.name()])
+            .arg(output)
+            .arg(input)
+            .args(["--", "-merge=1"])
+            .env("RUSTFLAGS", self.config.rust_flags())
+            .envs(self.config.harness_envs())
+            .current_dir(self.work_dir.join("fuzz"))
+            .spawn()
+            .context(format!(
+                "error starting libfuzzer merge for {}",
+                target.name()
             ))?
             .wait()
             .context(format!(
-                "error while waiting for {:?} running {}",
-                self.name,
+                "error while waiting for libfuzzer merge for {}",
                 target.name()
             ))?;
 
-        if !fuzzer_bin.success() {
+        if !cmin_cmd.success() {
             return Err(FuzzerQuit.into());
         }
         Ok(())
     }
+
+    /// Minimize the crash with `cargo fuzz tmin`
+    fn minimize(&self, target: Targets, crash: &Path, output: &Path) -> Result<(), Error> {
+        self.prepare(target)?;
+
+        let tmin_cmd = Command::new("cargo") // "+nightly",
+            .args(["fuzz", "tmin", &target.name()])
+            .arg(crash)
+            .env("RUSTFLAGS", self.config.rust_flags())
+            .envs(self.config.harness_envs())
+            .current_dir(self.work_dir.join("fuzz"))
+            .spawn()
+            .context(format!(
+                "error starting cargo fuzz tmin for {}",
+                target.name()
+            ))?
+            .wait()
+            .context(format!(
+                "error while waiting for cargo fuzz tmin for {}",
+                target.name()
+            ))?;
+
+        if !tmin_cmd.success() {
+            return Err(FuzzerQuit.into());
+        }
+
+        // cargo fuzz saves the smallest input as `minimized-from-<hash>`
+        // inside the artifacts of the target
+        let mut minimized = Vec::new();
+        for entry in fs::read_dir(self.crashes_dir(target)?)? {
+            let entry = entry?;
+            if entry
+                .file_name()
+                .to_string_lossy()
+                .starts_with("minimized-from-")
+            {
+                minimized.push((entry.metadata()?.modified()?, entry.path()));
+            }
+        }
+        match minimized.into_iter().max() {
+            Some((_, path)) => {
+                fs::copy(&path, output)?;
+                Ok(())
+            }
+            None => bail!("cargo fuzz tmin didn't save any minimized input"),
+        }
+    }
 }
 
 /// Add new target for libfuzzer using `cargo fuzz add`
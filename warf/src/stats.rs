@@ -0,0 +1,251 @@
+use failure::{Error, ResultExt};
+use regex::Regex;
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::env::workspace_dir;
+use crate::targets::Targets;
+
+/// Statistics of a fuzzing session, common to all the fuzzers
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Stats {
+    pub execs_per_sec: f64,
+    /// Edges (or branches) covered
+    pub edges: u64,
+    pub corpus_size: u64,
+    pub crashes: u64,
+    pub timeouts: u64,
+    /// Duration of the session, in seconds
+    pub elapsed: u64,
+}
+
+impl Stats {
+    /// One line summary of the session
+    pub fn summary(&self, target: &str, fuzzer: &str) -> String {
+        format!(
+            "[WARF] {} ({}): {:.0} execs/s, {} edges, {} inputs, {} crashes, {} timeouts, {}s",
+            target,
+            fuzzer,
+            self.execs_per_sec,
+            self.edges,
+            self.corpus_size,
+            self.crashes,
+            self.timeouts,
+            self.elapsed
+        )
+    }
+
+    /// Add the statistics of another job of the same session
+    pub fn add(&mut self, job: &Stats) {
+        self.execs_per_sec += job.execs_per_sec;
+        // jobs share their corpus, so mostly the same edges
+        self.edges = self.edges.max(job.edges);
+        self.corpus_size = self.corpus_size.max(job.corpus_size);
+        self.crashes += job.crashes;
+        self.timeouts += job.timeouts;
+    }
+}
+
+/// Last value captured by `re` inside `text`
+fn last_capture<T: std::str::FromStr>(re: &Regex, text: &str) -> Option<T> {
+    re.captures_iter(text)
+        .last()
+        .and_then(|caps| caps[1].parse().ok())
+}
+
+/// Statistics from the logs of honggfuzz (`--logfile`)
+///
+/// NOTE: the speed, crashes and timeouts are only logged at the end of
+/// the session, inside the `Summary` line
+pub fn parse_hfuzz_log(log: &str) -> Stats {
+    Stats {
+        execs_per_sec: last_capture(regex!(r"\bspeed:(\d+)"), log).unwrap_or_default(),
+        // `Tot:` (or `Cur:`) lists the instructions, branches, hardware
+        // branches, edges, ip and cmp counters
+        edges: last_capture(regex!(r"(?:Tot|Cur):\d+/\d+/\d+/(\d+)/"), log).unwrap_or_default(),
+        crashes: last_capture(regex!(r"\bcrashes_count:(\d+)"), log).unwrap_or_default(),
+        timeouts: last_capture(regex!(r"\btimeout_count:(\d+)"), log).unwrap_or_default(),
+        ..Stats::default()
+    }
+}
+
+/// Statistics from the `fuzzer_stats` file of afl (or afl++)
+pub fn parse_afl_stats(fuzzer_stats: &str) -> Stats {
+    let value = |keys: &[&str]| -> Option<&str> {
+        fuzzer_stats.lines().find_map(|line| {
+            let mut kv = line.splitn(2, ':');
+            let key = kv.next()?.trim();
+            let value = kv.next()?.trim();
+            if keys.contains(&key) {
+                Some(value)
+            } else {
+                None
+            }
+        })
+    };
+    let number =
+        |keys: &[&str]| -> u64 { value(keys).and_then(|v| v.parse().ok()).unwrap_or_default() };
+    // afl only gives the density of its 64k entries bitmap
+    let edges = match value(&["edges_found"]) {
+        Some(edges) => edges.parse().unwrap_or_default(),
+        None => value(&["bitmap_cvg"])
+            .and_then(|v| v.trim_end_matches('%').parse::<f64>().ok())
+            .map(|cvg| (cvg * 65536.0 / 100.0) as u64)
+            .unwrap_or_default(),
+    };
+    Stats {
+        execs_per_sec: value(&["execs_per_sec"])
+            .and_then(|v| v.parse().ok())
+            .unwrap_or_default(),
+        edges,
+        corpus_size: number(&["paths_total", "corpus_count"]),
+        crashes: number(&["unique_crashes", "saved_crashes"]),
+        timeouts: number(&["unique_hangs", "saved_hangs"]),
+        ..Stats::default()
+    }
+}
+
+/// Statistics from the output of libFuzzer, with `-print_final_stats=1`
+pub fn parse_libfuzzer_log(log: &str) -> Stats {
+    // e.g. `#1024 pulse  cov: 1234 ft: 2345 corp: 56/1234b lim: 4 exec/s: 512 rss: 40Mb`
+    let status = regex!(r"#\d+\s+\w+\s+cov: (\d+) ft: \d+ corp: (\d+)/\S+.*?exec/s: (\d+)");
+    let last = status.captures_iter(log).last();
+    let field = |i: usize| -> u64 {
+        last.as_ref()
+            .and_then(|caps| caps[i].parse().ok())
+            .unwrap_or_default()
+    };
+    let artifacts = |re: &Regex| -> u64 { re.find_iter(log).count() as u64 };
+    Stats {
+        execs_per_sec: last_capture(regex!(r"stat::average_exec_per_sec:\s+(\d+)"), log)
+            .unwrap_or(field(3) as f64),
+        edges: field(1),
+        corpus_size: field(2),
+        crashes: artifacts(regex!(r"Test unit written to \S*/(?:crash|oom|leak)-")),
+        timeouts: artifacts(regex!(r"Test unit written to \S*/timeout-")),
+        ..Stats::default()
+    }
+}
+
+/// Statistics of a libFuzzer session from its output and, with `-jobs`,
+/// the `fuzz-<n>.log` of every job
+///
+/// NOTE: with `-jobs`, the main process copies the logs of the jobs into
+/// its output, so only the logs of the jobs are read
+pub fn parse_libfuzzer_session(log: &str, job_logs: &[String]) -> Stats {
+    if job_logs.is_empty() {
+        return parse_libfuzzer_log(log);
+    }
+    let mut stats = Stats::default();
+    for job_log in job_logs {
+        stats.add(&parse_libfuzzer_log(job_log));
+    }
+    stats
+}
+
+/// File where the statistics of all the sessions of the target are recorded
+fn stats_path(target: Targets) -> Result<PathBuf, Error> {
+    Ok(workspace_dir()?
+        .join("stats")
+        .join(format!("{}.jsonl", target.name())))
+}
+
+/// Append the statistics of a session to `workspace/stats/<target>.jsonl`
+pub fn record_stats(target: Targets, fuzzer: &str, stats: &Stats) -> Result<(), Error> {
+    let path = stats_path(target)?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).context(format!("unable to create {} dir", dir.display()))?;
+    }
+    let time = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    let line = format!(
+        "{{\"time\":{},\"target\":\"{}\",\"fuzzer\":\"{}\",\"execs_per_sec\":{:.2},\"edges\":{},\"corpus_size\":{},\"crashes\":{},\"timeouts\":{},\"elapsed\":{}}}\n",
+        time,
+        target.name(),
+        fuzzer,
+        stats.execs_per_sec,
+        stats.edges,
+        stats.corpus_size,
+        stats.crashes,
+        stats.timeouts,
+        stats.elapsed
+    );
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .context(format!("error writing {}", path.display()))?;
+    file.write_all(line.as_bytes())?;
+    Ok(())
+}
+
+/// Summary of the last session recorded for the target, if any
+pub fn last_summary(target: Targets) -> Option<String> {
+    let records = fs::read_to_string(stats_path(target).ok()?).ok()?;
+    let record = records.lines().last()?;
+    // `"key":"string"` or `"key":number`
+    let fields: HashMap<&str, &str> = regex!(r#""(\w+)":"?([^",}]*)"?"#)
+        .captures_iter(record)
+        .filter_map(|caps| Some((caps.get(1)?.as_str(), caps.get(2)?.as_str())))
+        .collect();
+    let number = |key: &str| -> u64 {
+        fields
+            .get(key)
+            .and_then(|v| v.parse().ok())
+            .unwrap_or_default()
+    };
+    let stats = Stats {
+        execs_per_sec: fields
+            .get("execs_per_sec")
+            .and_then(|v| v.parse().ok())
+            .unwrap_or_default(),
+        edges: number("edges"),
+        corpus_size: number("corpus_size"),
+        crashes: number("crashes"),
+        timeouts: number("timeouts"),
+        elapsed: number("elapsed"),
+    };
+    Some(stats.summary(&target.name(), fields.get("fuzzer")?))
+}
+
+/// Number of files inside the directory
+pub fn count_files(dir: &Path) -> u64 {
+    fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .filter(|entry| entry.path().is_file())
+                .count() as u64
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const JOB_LOG: &str = "\
+#1024\tpulse  cov: 120 ft: 200 corp: 10/512b lim: 4 exec/s: 500 rss: 40Mb
+==42== ERROR: libFuzzer: deadly signal
+artifact_prefix='crashes/'; Test unit written to crashes/crash-0123456789abcdef
+";
+
+    #[test]
+    fn libfuzzer_jobs_are_not_counted_twice() {
+        let log = format!(
+            "Running 1 jobs in 1 workers\n\
+             ================== Job 0 exited with exit code 1 ============\n\
+             {}",
+            JOB_LOG
+        );
+        let job = parse_libfuzzer_log(JOB_LOG);
+        assert_eq!(job.crashes, 1);
+        assert_eq!(parse_libfuzzer_session(&log, &[JOB_LOG.to_string()]), job);
+        assert_eq!(parse_libfuzzer_session(JOB_LOG, &[]), job);
+    }
+}
@@ -1,27 +1,43 @@
-use failure::Error;
+use failure::{Error, ResultExt};
+use std::fs;
+use std::path::PathBuf;
 use strum::IntoEnumIterator;
 
-use crate::env::{targets_dir, workspace_dir};
-use crate::utils::copy_dir;
+use crate::env::{corpora_dir, targets_dir, workspace_dir};
+use crate::utils::{copy_dir, did_you_mean, sync_inputs};
 
 #[derive(Copy, Clone, Debug, EnumIter)]
 pub enum Targets {
     // wasmi
     WasmiValidate,
     WasmiInstantiate,
+    WasmiInstantiateSmith,
+    WasmiExecute,
     // parity_wasm
     ParityWasmDeserialize,
+    ParityWasmRoundtrip,
     // wasmer
     WasmerValidate,
     WasmerCompileClif,
+    WasmerCompileClifSmith,
     WasmerCompileSinglepass,
+    WasmerCompileSinglepassSmith,
     WasmerInstantiate,
+    WasmerInstantiateSmith,
     // wasmtime
     WasmtimeValidate,
     WasmtimeValidateAllFeat,
     WasmtimeCompile,
+    WasmtimeCompileSmith,
     WasmtimeCompileAllCranelift,
+    WasmtimeCompileAllCraneliftSmith,
     WasmtimeInstantiateAllCranelift,
+    WasmtimeInstantiateAllCraneliftSmith,
+    // cranelift
+    CraneliftTranslate,
+    CraneliftCompileX86_64,
+    CraneliftCompileAarch64,
+    CraneliftCompileS390x,
     // wasmparser
     WasmparserParser,
     WasmparserValidate,
@@ -29,27 +45,46 @@ pub enum Targets {
     // binaryen_ffi
     BinaryenFfi,
     BinaryenOptimizeFfi,
+    BinaryenRoundtripFfi,
     // wabt_ffi
     WabtWasm2watAllFeatFfi,
     WabtValidateFfi,
     WabtWat2WasmAllFeatFfi,
+    WabtRoundtripFfi,
     // wasmprinter
     WasmprinterParser,
+    WasmprinterWatRoundtrip,
     // wain
     WainParser,
     WainValidate,
+    WainExecute,
     // wat
     WatParser,
     // wast
     WastParser,
+    WastExecute,
     // wasm3
     Wasm3Parser,
+    Wasm3Execute,
     // fizzy,
     FizzyValidate,
+    FizzyInstantiate,
+    FizzyExecute,
     // differential fuzzing
     DiffParsing,
+    DiffParsingSmith,
     DiffValidateAllFeat,
+    DiffValidateAllFeatSmith,
+    DiffValidateMvp,
+    DiffValidateSimd,
+    DiffValidateThreads,
+    DiffValidateReferenceTypes,
+    DiffValidateBulkMemory,
+    DiffValidateMultiValue,
     DiffInstantiate,
+    DiffInstantiateSmith,
+    DiffExecute,
+    DiffExecuteSmith,
     DiffWatParsing,
 }
 
@@ -59,19 +94,35 @@ impl Targets {
             // wasmi
             Targets::WasmiValidate => "wasmi_validate",
             Targets::WasmiInstantiate => "wasmi_instantiate",
+            Targets::WasmiInstantiateSmith => "wasmi_instantiate_smith",
+            Targets::WasmiExecute => "wasmi_execute",
             // parity_wasm
             Targets::ParityWasmDeserialize => "parity_wasm_deserialize",
+            Targets::ParityWasmRoundtrip => "parity_wasm_roundtrip",
             // wasmer
             Targets::WasmerValidate => "wasmer_validate",
             Targets::WasmerCompileClif => "wasmer_compile_clif",
+            Targets::WasmerCompileClifSmith => "wasmer_compile_clif_smith",
             Targets::WasmerCompileSinglepass => "wasmer_compile_singlepass",
+            Targets::WasmerCompileSinglepassSmith => "wasmer_compile_singlepass_smith",
             Targets::WasmerInstantiate => "wasmer_instantiate",
+            Targets::WasmerInstantiateSmith => "wasmer_instantiate_smith",
             // wasmtime
             Targets::WasmtimeValidate => "wasmtime_validate",
             Targets::WasmtimeValidateAllFeat => "wasmtime_validate_all_feat",
             Targets::WasmtimeCompile => "wasmtime_compile",
+            Targets::WasmtimeCompileSmith => "wasmtime_compile_smith",
             Targets::WasmtimeCompileAllCranelift => "wasmtime_compile_all_cranelift",
+            Targets::WasmtimeCompileAllCraneliftSmith => "wasmtime_compile_all_cranelift_smith",
             Targets::WasmtimeInstantiateAllCranelift => "wasmtime_instantiate_all_cranelift",
+            Targets::WasmtimeInstantiateAllCraneliftSmith => {
+                "wasmtime_instantiate_all_cranelift_smith"
+            }
+            // cranelift
+            Targets::CraneliftTranslate => "cranelift_translate",
+            Targets::CraneliftCompileX86_64 => "cranelift_compile_x86_64",
+            Targets::CraneliftCompileAarch64 => "cranelift_compile_aarch64",
+            Targets::CraneliftCompileS390x => "cranelift_compile_s390x",
             // wasmparser
             Targets::WasmparserParser => "wasmparser_parser",
             Targets::WasmparserValidate => "wasmparser_validate",
@@ -79,27 +130,46 @@ impl Targets {
             // binaryen_ffi
             Targets::BinaryenFfi => "binaryen_ffi",
             Targets::BinaryenOptimizeFfi => "binaryen_optimize_ffi",
+            Targets::BinaryenRoundtripFfi => "binaryen_roundtrip_ffi",
             // wabt_ffi
             Targets::WabtWasm2watAllFeatFfi => "wabt_wasm2wat_all_feat_ffi",
             Targets::WabtValidateFfi => "wabt_validate_ffi",
             Targets::WabtWat2WasmAllFeatFfi => "wabt_wat2wasm_ffi",
+            Targets::WabtRoundtripFfi => "wabt_roundtrip_ffi",
             // wasmprinter
             Targets::WasmprinterParser => "wasmprinter_parser",
+            Targets::WasmprinterWatRoundtrip => "wasmprinter_wat_roundtrip",
             // wain
             Targets::WainParser => "wain_parser",
             Targets::WainValidate => "wain_validate",
+            Targets::WainExecute => "wain_execute",
             // wat
             Targets::WatParser => "wat_parser",
             // wast
             Targets::WastParser => "wast_parser",
+            Targets::WastExecute => "wast_execute",
             // wasm3
             Targets::Wasm3Parser => "wasm3_parser_ffi",
+            Targets::Wasm3Execute => "wasm3_execute_ffi",
             // fizzy
             Targets::FizzyValidate => "fizzy_validate",
+            Targets::FizzyInstantiate => "fizzy_instantiate",
+            Targets::FizzyExecute => "fizzy_execute",
             // differential fuzzing
             Targets::DiffParsing => "diff_parsing",
+            Targets::DiffParsingSmith => "diff_parsing_smith",
             Targets::DiffValidateAllFeat => "diff_all_validate",
+            Targets::DiffValidateAllFeatSmith => "diff_all_validate_smith",
+            Targets::DiffValidateMvp => "diff_validate_mvp",
+            Targets::DiffValidateSimd => "diff_validate_simd",
+            Targets::DiffValidateThreads => "diff_validate_threads",
+            Targets::DiffValidateReferenceTypes => "diff_validate_reference_types",
+            Targets::DiffValidateBulkMemory => "diff_validate_bulk_memory",
+            Targets::DiffValidateMultiValue => "diff_validate_multi_value",
             Targets::DiffInstantiate => "diff_instantiate",
+            Targets::DiffInstantiateSmith => "diff_instantiate_smith",
+            Targets::DiffExecute => "diff_execute",
+            Targets::DiffExecuteSmith => "diff_execute_smith",
             Targets::DiffWatParsing => "diff_wat_parsing",
         }
         .to_string()
@@ -110,19 +180,33 @@ impl Targets {
             // wasmi
             Targets::WasmiValidate
             | Targets::WasmiInstantiate
+            | Targets::WasmiInstantiateSmith
+            | Targets::WasmiExecute
             // parity_wasm
             | Targets::ParityWasmDeserialize
+            | Targets::ParityWasmRoundtrip
             // wasmer
             | Targets::WasmerValidate
             | Targets::WasmerCompileClif
+            | Targets::WasmerCompileClifSmith
             | Targets::WasmerCompileSinglepass
+            | Targets::WasmerCompileSinglepassSmith
             | Targets::WasmerInstantiate
+            | Targets::WasmerInstantiateSmith
             // wasmtime
             | Targets::WasmtimeValidate
             | Targets::WasmtimeValidateAllFeat
             | Targets::WasmtimeCompile
+            | Targets::WasmtimeCompileSmith
             | Targets::WasmtimeCompileAllCranelift
+            | Targets::WasmtimeCompileAllCraneliftSmith
             | Targets::WasmtimeInstantiateAllCranelift
+            | Targets::WasmtimeInstantiateAllCraneliftSmith
+            // cranelift
+            | Targets::CraneliftTranslate
+            | Targets::CraneliftCompileX86_64
+            | Targets::CraneliftCompileAarch64
+            | Targets::CraneliftCompileS390x
             // wasmparser
             | Targets::WasmparserParser
             | Targets::WasmparserValidate
@@ -130,27 +214,42 @@ impl Targets {
             // binaryen_ffi
             | Targets::BinaryenFfi
             | Targets::BinaryenOptimizeFfi
+            | Targets::BinaryenRoundtripFfi
             // wabt_ffi
             | Targets::WabtWasm2watAllFeatFfi
-            | Targets::WabtValidateFfi => "wasm",
+            | Targets::WabtValidateFfi
+            | Targets::WabtRoundtripFfi => "wasm",
             Targets::WabtWat2WasmAllFeatFfi => "wat",
             // wasmprinter
             Targets::WasmprinterParser
+            | Targets::WasmprinterWatRoundtrip
             // wain
             | Targets::WainParser
-            | Targets::WainValidate => "wasm",
+            | Targets::WainValidate
+            | Targets::WainExecute => "wasm",
             // wat
             Targets::WatParser => "wat",
             // wast
-            Targets::WastParser => "wast",
+            Targets::WastParser | Targets::WastExecute => "wast",
             // wasm3
-            Targets::Wasm3Parser => "wasm",
+            Targets::Wasm3Parser | Targets::Wasm3Execute => "wasm",
             // fizzy
-            Targets::FizzyValidate => "wasm",
+            Targets::FizzyValidate | Targets::FizzyInstantiate | Targets::FizzyExecute => "wasm",
             // differential fuzzing
             Targets::DiffParsing
+            | Targets::DiffParsingSmith
             | Targets::DiffValidateAllFeat
-            | Targets::DiffInstantiate => "wasm",
+            | Targets::DiffValidateAllFeatSmith
+            | Targets::DiffValidateMvp
+            | Targets::DiffValidateSimd
+            | Targets::DiffValidateThreads
+            | Targets::DiffValidateReferenceTypes
+            | Targets::DiffValidateBulkMemory
+            | Targets::DiffValidateMultiValue
+            | Targets::DiffInstantiate
+            | Targets::DiffInstantiateSmith
+            | Targets::DiffExecute
+            | Targets::DiffExecuteSmith => "wasm",
             Targets::DiffWatParsing => "wat",
         }
         .to_string()
@@ -161,19 +260,33 @@ impl Targets {
             // wasmi
             Targets::WasmiValidate
             | Targets::WasmiInstantiate
+            | Targets::WasmiInstantiateSmith
+            | Targets::WasmiExecute
             // parity_wasm
             | Targets::ParityWasmDeserialize
+            | Targets::ParityWasmRoundtrip
             // wasmer
             | Targets::WasmerValidate
             | Targets::WasmerCompileClif
+            | Targets::WasmerCompileClifSmith
             | Targets::WasmerCompileSinglepass
+            | Targets::WasmerCompileSinglepassSmith
             | Targets::WasmerInstantiate
+            | Targets::WasmerInstantiateSmith
             // wasmtime
             | Targets::WasmtimeValidate
             | Targets::WasmtimeValidateAllFeat
             | Targets::WasmtimeCompile
+            | Targets::WasmtimeCompileSmith
             | Targets::WasmtimeCompileAllCranelift
+            | Targets::WasmtimeCompileAllCraneliftSmith
             | Targets::WasmtimeInstantiateAllCranelift
+            | Targets::WasmtimeInstantiateAllCraneliftSmith
+            // cranelift
+            | Targets::CraneliftTranslate
+            | Targets::CraneliftCompileX86_64
+            | Targets::CraneliftCompileAarch64
+            | Targets::CraneliftCompileS390x
             // wasmparser
             | Targets::WasmparserParser
             | Targets::WasmparserValidate
@@ -181,27 +294,46 @@ impl Targets {
             // binaryen_ffi
             | Targets::BinaryenFfi
             | Targets::BinaryenOptimizeFfi
+            | Targets::BinaryenRoundtripFfi
             // wabt_ffi
             | Targets::WabtWasm2watAllFeatFfi
             | Targets::WabtValidateFfi
             | Targets::WabtWat2WasmAllFeatFfi
+            | Targets::WabtRoundtripFfi
             // wasmprinter
             | Targets::WasmprinterParser
+            | Targets::WasmprinterWatRoundtrip
             // wain
             | Targets::WainParser
             | Targets::WainValidate
+            | Targets::WainExecute
             // wat 
             | Targets::WatParser
             // wast
             | Targets::WastParser
+            | Targets::WastExecute
             // wasm3
             | Targets::Wasm3Parser
+            | Targets::Wasm3Execute
             // fizzy
             | Targets::FizzyValidate
+            | Targets::FizzyInstantiate
+            | Targets::FizzyExecute
             // differential fuzzing
             | Targets::DiffParsing
+            | Targets::DiffParsingSmith
             | Targets::DiffValidateAllFeat
+            | Targets::DiffValidateAllFeatSmith
+            | Targets::DiffValidateMvp
+            | Targets::DiffValidateSimd
+            | Targets::DiffValidateThreads
+            | Targets::DiffValidateReferenceTypes
+            | Targets::DiffValidateBulkMemory
+            | Targets::DiffValidateMultiValue
             | Targets::DiffInstantiate
+            | Targets::DiffInstantiateSmith
+            | Targets::DiffExecute
+            | Targets::DiffExecuteSmith
             | Targets::DiffWatParsing => "template.rs",
         }
         .to_string()
@@ -212,19 +344,33 @@ impl Targets {
             // wasmi
             Targets::WasmiValidate
             | Targets::WasmiInstantiate
+            | Targets::WasmiInstantiateSmith
+            | Targets::WasmiExecute
             // parity_wasm
             | Targets::ParityWasmDeserialize
+            | Targets::ParityWasmRoundtrip
             // wasmer
             | Targets::WasmerValidate
             | Targets::WasmerCompileClif
+            | Targets::WasmerCompileClifSmith
             | Targets::WasmerCompileSinglepass
+            | Targets::WasmerCompileSinglepassSmith
             | Targets::WasmerInstantiate
+            | Targets::WasmerInstantiateSmith
             // wasmtime
             | Targets::WasmtimeValidate
             | Targets::WasmtimeValidateAllFeat
             | Targets::WasmtimeCompile
+            | Targets::WasmtimeCompileSmith
             | Targets::WasmtimeCompileAllCranelift
+            | Targets::WasmtimeCompileAllCraneliftSmith
             | Targets::WasmtimeInstantiateAllCranelift
+            | Targets::WasmtimeInstantiateAllCraneliftSmith
+            // cranelift
+            | Targets::CraneliftTranslate
+            | Targets::CraneliftCompileX86_64
+            | Targets::CraneliftCompileAarch64
+            | Targets::CraneliftCompileS390x
             // wasmparser
             | Targets::WasmparserParser
             | Targets::WasmparserValidate
@@ -232,27 +378,46 @@ impl Targets {
             // binaryen_ffi
             | Targets::BinaryenFfi
             | Targets::BinaryenOptimizeFfi
+            | Targets::BinaryenRoundtripFfi
             // wabt_ffi
             | Targets::WabtWasm2watAllFeatFfi
             | Targets::WabtValidateFfi
             | Targets::WabtWat2WasmAllFeatFfi
+            | Targets::WabtRoundtripFfi
             // wasmprinter
             | Targets::WasmprinterParser
+            | Targets::WasmprinterWatRoundtrip
             // wain
             | Targets::WainParser
             | Targets::WainValidate
+            | Targets::WainExecute
             // wat
             | Targets::WatParser
             // wast
             | Targets::WastParser
+            | Targets::WastExecute
             // wasm3
             | Targets::Wasm3Parser
+            | Targets::Wasm3Execute
             // fizzy
             | Targets::FizzyValidate
+            | Targets::FizzyInstantiate
+            | Targets::FizzyExecute
             // differential fuzzing
             | Targets::DiffParsing
+            | Targets::DiffParsingSmith
             | Targets::DiffValidateAllFeat
+            | Targets::DiffValidateAllFeatSmith
+            | Targets::DiffValidateMvp
+            | Targets::DiffValidateSimd
+            | Targets::DiffValidateThreads
+            | Targets::DiffValidateReferenceTypes
+            | Targets::DiffValidateBulkMemory
+            | Targets::DiffValidateMultiValue
             | Targets::DiffInstantiate
+            | Targets::DiffInstantiateSmith
+            | Targets::DiffExecute
+            | Targets::DiffExecuteSmith
             | Targets::DiffWatParsing => "rust",
         }
         .to_string()
@@ -263,6 +428,56 @@ pub fn get_targets() -> Vec<String> {
     Targets::iter().map(|x| x.name()).collect()
 }
 
+/// Find the target called `name`, suggesting the closest one if unknown
+pub fn find_target(name: &str) -> Result<Targets, Error> {
+    match Targets::iter().find(|x| x.name() == name) {
+        None => bail!(
+            "Don't know target `{}`. {}",
+            name,
+            if let Some(alt) = did_you_mean(&name, &get_targets()) {
+                format!("Did you mean `{}`?", alt)
+            } else {
+                "".into()
+            }
+        ),
+        Some(t) => Ok(t),
+    }
+}
+
+/// Corpus of the target alone, where all the fuzzers save their new inputs
+pub fn own_corpus_dir(target: Targets) -> Result<PathBuf, Error> {
+    Ok(corpora_dir()?.join("targets").join(target.name()))
+}
+
+/// Corpora shared by all the targets of the same type, only used as seeds
+pub fn kind_corpus_dir(target: Targets) -> Result<PathBuf, Error> {
+    Ok(corpora_dir()?.join(target.corpora()))
+}
+
+/// Corpus used to fuzz the target: its own corpus, seeded from the
+/// corpora of its type the first time
+pub fn target_corpus_dir(target: Targets) -> Result<PathBuf, Error> {
+    let own = own_corpus_dir(target)?;
+    if own.is_dir() {
+        return Ok(own);
+    }
+    // Seeded aside then renamed, a seeding interrupted halfway would
+    // otherwise be used as the corpus of the target
+    let seeding = own.with_extension("seeding");
+    if seeding.exists() {
+        fs::remove_dir_all(&seeding).context(format!("error removing {}", seeding.display()))?;
+    }
+    let kind = kind_corpus_dir(target)?;
+    if kind.is_dir() {
+        sync_inputs(&kind, &seeding)?;
+    } else {
+        fs::create_dir_all(&seeding)
+            .context(format!("unable to create {} dir", seeding.display()))?;
+    }
+    fs::rename(&seeding, &own).context(format!("error creating {}", own.display()))?;
+    Ok(own)
+}
+
 pub fn prepare_targets_workspace() -> Result<(), Error> {
     let from = targets_dir()?;
     let workspace = workspace_dir()?;
@@ -0,0 +1,209 @@
+use failure::{Error, ResultExt};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use strum::IntoEnumIterator;
+
+use crate::debug::build_debug_target;
+use crate::env::workspace_dir;
+use crate::fuzzers::{all_fuzzers, FuzzerConfig};
+use crate::targets::Targets;
+use crate::utils::hash;
+
+/// Number of stack frames, outside of the standard library, used
+/// to identify a crash
+const TOP_FRAMES: usize = 3;
+
+/// Directory where the crashes of all the fuzzers are gathered
+pub fn crashes_dir() -> Result<PathBuf, Error> {
+    let p = workspace_dir()?.join("crashes");
+    Ok(p)
+}
+
+/// Crashing inputs found by all the fuzzers for this target
+///
+/// NOTE: text files (e.g. honggfuzz report, afl README) are skipped
+fn collect_inputs(target: Targets) -> Result<Vec<PathBuf>, Error> {
+    let mut inputs = Vec::new();
+    for fuzzer in all_fuzzers(FuzzerConfig::default())? {
+        let dir = fuzzer.crashes_dir(target)?;
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(_) => continue,
+        };
+        for entry in entries {
+            let path = entry?.path();
+            let is_text = path
+                .extension()
+                .filter(|ext| ext.eq_ignore_ascii_case("txt"))
+                .is_some();
+            if path.is_file() && !is_text {
+                inputs.push(path);
+            }
+        }
+    }
+    Ok(inputs)
+}
+
+/// Replay the input with the debug binary, returning the signature
+/// of the crash or `None` if the input doesn't crash anymore.
+///
+/// Disagreements of differential targets are crashes for the
+/// fuzzers but not for the debug binary, that only prints them.
+pub fn replay(debug_bin: &Path, input: &Path) -> Result<Option<(String, String)>, Error> {
+    let output = Command::new(debug_bin)
+        .arg(input)
+        .env("RUST_BACKTRACE", "1")
+        .output()
+        .context(format!("error replaying {}", input.display()))?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let report = format!("{}\n{}", stdout, stderr);
+
+    let signature = if !output.status.success() {
+        let message = panic_message(&stderr)
+            .unwrap_or_else(|| format!("no panic message, {}", output.status));
+        let mut signature = normalize(&message);
+        for frame in top_frames(&stderr) {
+            signature.push_str("\n  at ");
+            signature.push_str(&frame);
+        }
+        signature
+    } else if stdout.contains("implementations disagree") {
+        let outcome = stdout
+            .split("outcome: ")
+            .nth(1)
+            .and_then(|s| s.split("No crash").next())
+            .unwrap_or_default();
+        normalize(outcome.trim_end())
+    } else {
+        return Ok(None);
+    };
+    Ok(Some((signature, report)))
+}
+
+/// Message of the panic printed on stderr, supporting the formats
+/// `panicked at 'msg', file:line:col` and `panicked at file:line:col:\nmsg`
+fn panic_message(stderr: &str) -> Option<String> {
+    let start = stderr.find("panicked at ")? + "panicked at ".len();
+    let rest = &stderr[start..];
+    if rest.starts_with('\'') {
+        let end = rest.rfind("', ")?;
+        return Some(rest[1..end].to_string());
+    }
+    let message: Vec<&str> = rest
+        .lines()
+        .skip(1)
+        .take_while(|line| !line.starts_with("note:") && !line.starts_with("stack backtrace:"))
+        .collect();
+    Some(message.join("\n"))
+}
+
+/// Remove what differs between two inputs hitting the same bug
+/// (e.g. offsets, indexes, addresses)
+///
+/// NOTE: only standalone numbers are replaced, not the digits of
+/// identifiers (e.g. `i32`, `f64x2`)
+fn normalize(message: &str) -> String {
+    let message = regex!(r"\b0x[0-9a-fA-F]+\b").replace_all(message, "0xN");
+    regex!(r"\b\d+\b").replace_all(&message, "N").to_string()
+}
+
+/// First functions of the backtrace, without the panic machinery
+/// and the hash of the symbols
+fn top_frames(stderr: &str) -> Vec<String> {
+    let frame = regex!(r"^\s*\d+: (.+?)(::h[0-9a-f]{16})?$");
+    stderr
+        .lines()
+        .filter_map(|line| frame.captures(line))
+        .map(|caps| caps[1].to_string())
+        .filter(|name| {
+            let name = name.trim_start_matches('<');
+            !name.starts_with("std::")
+                && !name.starts_with("core::")
+                && !name.starts_with("alloc::")
+                && !name.starts_with("rust_")
+                && !name.starts_with("__rust")
+                && !name.starts_with("backtrace::")
+                && !name.contains(" as core::")
+                && !name.contains(" as alloc::")
+                && !name.contains(" as std::")
+        })
+        .take(TOP_FRAMES)
+        .collect()
+}
+
+/// Triage the crashes of one target into `workspace/crashes/<target>/`,
+/// one folder per unique crash containing its inputs and reports.
+fn triage_target(target: Targets) -> Result<(), Error> {
+    let inputs = collect_inputs(target)?;
+    if inputs.is_empty() {
+        return Ok(());
+    }
+    println!(
+        "[WARF] Triage: {} crashing inputs for {}",
+        inputs.len(),
+        target.name()
+    );
+    let debug_bin = build_debug_target(target)?;
+
+    let mut crashes: BTreeMap<String, usize> = BTreeMap::new();
+    let mut not_reproduced = 0;
+    for input in inputs {
+        let (signature, report) = match replay(&debug_bin, &input)? {
+            Some(crash) => crash,
+            None => {
+                not_reproduced += 1;
+                continue;
+            }
+        };
+        let data = fs::read(&input).context(format!("error reading {}", input.display()))?;
+
+        let crash_dir = crashes_dir()?.join(target.name()).join(hash(&signature));
+        fs::create_dir_all(&crash_dir)
+            .context(format!("unable to create {} dir", crash_dir.display()))?;
+        fs::write(crash_dir.join("signature.txt"), &signature)?;
+
+        // Identical inputs found by different fuzzers are kept once
+        let name = hash(&data);
+        fs::write(crash_dir.join(&name), &data)?;
+        fs::write(crash_dir.join(format!("{}.txt", name)), &report)?;
+
+        *crashes.entry(signature).or_default() += 1;
+    }
+
+    for (signature, count) in &crashes {
+        println!(
+            "[WARF] Triage: {} {} inputs\n{}",
+            hash(signature),
+            count,
+            signature
+        );
+    }
+    println!(
+        "[WARF] Triage: {} unique crashes for {} ({} inputs not reproduced)",
+        crashes.len(),
+        target.name(),
+        not_reproduced
+    );
+    Ok(())
+}
+
+/// Gather the crashes of all the fuzzers for the targets matching the filter
+pub fn run_triage(filter: Option<String>) -> Result<(), Error> {
+    let targets = Targets::iter().filter(|x| {
+        filter
+            .as_ref()
+            .map(|f| x.name().contains(f))
+            .unwrap_or(true)
+    });
+    for target in targets {
+        triage_target(target)?;
+    }
+    println!(
+        "[WARF] Triage: crashes saved in {}",
+        crashes_dir()?.display()
+    );
+    Ok(())
+}
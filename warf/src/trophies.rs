@@ -0,0 +1,271 @@
+use failure::{Error, ResultExt};
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::Duration;
+use strum::IntoEnumIterator;
+
+use crate::debug::build_debug_target;
+use crate::env::workspace_dir;
+use crate::targets::Targets;
+use crate::utils::wait_timeout;
+
+/// Maximum time to replay one trophy (e.g. huge cpu usage)
+const REPLAY_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Result of a trophy on one target
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Replay {
+    /// The target doesn't crash anymore
+    Fixed,
+    /// Crash, abort or panic of the debug binary
+    Crash,
+    /// The implementations disagree, without crash
+    Disagreement,
+    /// The replay didn't end before `REPLAY_TIMEOUT`
+    Timeout,
+}
+
+impl Replay {
+    fn symbol(self) -> &'static str {
+        match self {
+            Replay::Fixed => ".",
+            Replay::Crash => "X",
+            Replay::Disagreement => "D",
+            Replay::Timeout => "T",
+        }
+    }
+
+    /// Only crashes are counted as reproductions, disagreements and
+    /// timeouts are reported apart
+    fn reproduces(self) -> bool {
+        self == Replay::Crash
+    }
+}
+
+/// All the trophies (`*.wasm`) of the directory, sorted by name
+fn collect_trophies(dir: &Path) -> Result<Vec<PathBuf>, Error> {
+    let mut trophies = Vec::new();
+    for entry in fs::read_dir(dir).context(format!("error reading {}", dir.display()))? {
+        let path = entry?.path();
+        if path.extension().filter(|ext| *ext == "wasm").is_some() {
+            trophies.push(path);
+        }
+    }
+    trophies.sort();
+    Ok(trophies)
+}
+
+/// Replay the trophy with the debug binary in its own process, saving
+/// its output inside `log`
+fn replay_trophy(debug_bin: &Path, trophy: &Path, log: &Path) -> Result<Replay, Error> {
+    let stdout = fs::File::create(log).context(format!("error creating {}", log.display()))?;
+    let stderr = stdout.try_clone()?;
+    let mut child = Command::new(debug_bin)
+        .arg(trophy)
+        .env("RUST_BACKTRACE", "1")
+        .stdin(Stdio::null())
+        .stdout(stdout)
+        .stderr(stderr)
+        .spawn()
+        .context(format!("error replaying {}", trophy.display()))?;
+
+    let replay = match wait_timeout(&mut child, REPLAY_TIMEOUT)? {
+        None => Replay::Timeout,
+        Some(status) if !status.success() => Replay::Crash,
+        Some(_) => {
+            if fs::read_to_string(log)?.contains("implementations disagree") {
+                Replay::Disagreement
+            } else {
+                Replay::Fixed
+            }
+        }
+    };
+    Ok(replay)
+}
+
+/// Versions of the runtimes used by the targets, resolved in `Cargo.lock`
+fn resolved_versions(lock: &str) -> Vec<(String, String)> {
+    let field = |package: &str, key: &str| -> Option<String> {
+        package
+            .lines()
+            .find_map(|line| line.strip_prefix(&format!("{} = \"", key)))
+            .map(|value| value.trim_end_matches('"').to_string())
+    };
+    let packages: Vec<&str> = lock.split("[[package]]").skip(1).collect();
+    let targets = match packages
+        .iter()
+        .find(|p| field(p, "name").as_deref() == Some("fuzz-targets"))
+    {
+        Some(targets) => targets,
+        None => return Vec::new(),
+    };
+    let dependencies = match targets.split("dependencies = [").nth(1) {
+        Some(deps) => deps.split(']').next().unwrap_or_default(),
+        None => return Vec::new(),
+    };
+
+    let mut versions = Vec::new();
+    for dependency in dependencies.lines() {
+        // `"name"` or `"name version"` if multiple versions are locked
+        let mut dependency = dependency
+            .trim()
+            .trim_matches(|c| c == '"' || c == ',')
+            .split(' ');
+        let name = match dependency.next() {
+            Some(name) if !name.is_empty() => name.to_string(),
+            _ => continue,
+        };
+        let version = match dependency.next() {
+            Some(version) => version.to_string(),
+            None => packages
+                .iter()
+                .find(|p| field(p, "name").as_ref() == Some(&name))
+                .and_then(|p| field(p, "version"))
+                .unwrap_or_default(),
+        };
+        versions.push((name, version));
+    }
+    versions
+}
+
+/// Whether the trophies can be replayed on the target: it takes wasm
+/// modules as input, as is (no `_smith` targets), and isn't differential
+/// (a disagreement there has nothing to do with the trophy)
+fn replays_trophies(target: Targets) -> bool {
+    let name = target.name();
+    target.corpora() == "wasm" && !name.ends_with("_smith") && !name.starts_with("diff_")
+}
+
+/// Replay all the trophies of `dir` on every target replaying them,
+/// matching the filter, and print which ones still reproduce
+pub fn run_trophies(dir: String, filter: Option<String>) -> Result<(), Error> {
+    let trophies = collect_trophies(Path::new(&dir))?;
+    if trophies.is_empty() {
+        bail!("no trophy found in {}", dir);
+    }
+    let targets = Targets::iter().filter(|x| {
+        replays_trophies(*x)
+            && filter
+                .as_ref()
+                .map(|f| x.name().contains(f))
+                .unwrap_or(true)
+    });
+    let logs_dir = workspace_dir()?.join("trophies");
+
+    let mut matrix: Vec<(String, Vec<Replay>)> = Vec::new();
+    for target in targets {
+        let debug_bin = match build_debug_target(target) {
+            Ok(debug_bin) => debug_bin,
+            Err(_) => {
+                println!(
+                    "[WARF] Trophies: unable to build {}, skipped",
+                    target.name()
+                );
+                continue;
+            }
+        };
+        let target_logs = logs_dir.join(target.name());
+        fs::create_dir_all(&target_logs)
+            .context(format!("unable to create {} dir", target_logs.display()))?;
+
+        let mut row = Vec::new();
+        for trophy in &trophies {
+            let mut log = trophy.file_name().unwrap_or_default().to_os_string();
+            log.push(".txt");
+            row.push(replay_trophy(&debug_bin, trophy, &target_logs.join(log))?);
+        }
+        matrix.push((target.name(), row));
+    }
+    if matrix.is_empty() {
+        bail!("no target to replay the trophies on");
+    }
+
+    let mut report = String::new();
+    let _ = write!(report, "{:<45}", "");
+    for i in 1..=trophies.len() {
+        let _ = write!(report, "{:>4}", format!("T{}", i));
+    }
+    let _ = writeln!(report);
+    for (name, row) in &matrix {
+        let _ = write!(report, "{:<45}", name);
+        for replay in row {
+            let _ = write!(report, "{:>4}", replay.symbol());
+        }
+        let _ = writeln!(report);
+    }
+    let _ = writeln!(
+        report,
+        "\n(X: crash, D: disagreement, T: timeout, .: fixed)\n"
+    );
+
+    let mut reproduced = 0;
+    let (mut disagreements, mut timeouts) = (0, 0);
+    for (i, trophy) in trophies.iter().enumerate() {
+        let by = |replay: Replay| -> Vec<&str> {
+            matrix
+                .iter()
+                .filter(|(_, row)| row[i] == replay)
+                .map(|(name, _)| name.as_str())
+                .collect()
+        };
+        let (crashes, disagree, timeout) = (
+            by(Replay::Crash),
+            by(Replay::Disagreement),
+            by(Replay::Timeout),
+        );
+        if matrix.iter().any(|(_, row)| row[i].reproduces()) {
+            reproduced += 1;
+        }
+        disagreements += disagree.len();
+        timeouts += timeout.len();
+
+        let mut line = if crashes.is_empty() {
+            "fixed".to_string()
+        } else {
+            crashes.join(", ")
+        };
+        if !disagree.is_empty() {
+            let _ = write!(line, " (disagreement: {})", disagree.join(", "));
+        }
+        if !timeout.is_empty() {
+            let _ = write!(line, " (timeout: {})", timeout.join(", "));
+        }
+        let _ = writeln!(
+            report,
+            "{:>4} {}: {}",
+            format!("T{}", i + 1),
+            trophy.file_name().unwrap_or_default().to_string_lossy(),
+            line
+        );
+    }
+    let _ = writeln!(
+        report,
+        "\n{}/{} trophies still crash",
+        reproduced,
+        trophies.len()
+    );
+    let _ = writeln!(
+        report,
+        "{} disagreements and {} timeouts, not counted",
+        disagreements, timeouts
+    );
+
+    let lock = workspace_dir()?.join("debug").join("Cargo.lock");
+    if let Ok(lock) = fs::read_to_string(&lock) {
+        let _ = writeln!(report, "\nResolved versions:");
+        for (name, version) in resolved_versions(&lock) {
+            let _ = writeln!(report, "  {} {}", name, version);
+        }
+    }
+
+    fs::create_dir_all(&logs_dir)?;
+    fs::write(logs_dir.join("report.txt"), &report)?;
+    println!("{}", report);
+    println!(
+        "[WARF] Trophies: outputs of the replays saved in {}",
+        logs_dir.display()
+    );
+    Ok(())
+}
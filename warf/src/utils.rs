@@ -1,5 +1,18 @@
-use failure::Error;
-use std::path::PathBuf;
+use failure::{Error, ResultExt};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, ExitStatus};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// `&'static Regex` compiled the first time it is used, for regexes
+/// applied on every line or every input
+macro_rules! regex {
+    ($re:expr) => {{
+        static RE: std::sync::OnceLock<regex::Regex> = std::sync::OnceLock::new();
+        RE.get_or_init(|| regex::Regex::new($re).unwrap())
+    }};
+}
 
 pub fn copy_dir(from: PathBuf, to: PathBuf) -> Result<(), Error> {
     use fs_extra::dir::{copy, CopyOptions};
@@ -40,3 +53,61 @@ where
         Some((_, candidate)) => Some(candidate),
     }
 }
+
+/// Target triple of the host, the one used by default by `cargo fuzz`
+/// and `cargo hfuzz` to build the fuzz targets
+pub fn host_triple() -> Result<String, Error> {
+    let output = Command::new("rustc")
+        .arg("-vV")
+        .output()
+        .context("error running rustc")?;
+    let version = String::from_utf8_lossy(&output.stdout);
+    match version.lines().find_map(|l| l.strip_prefix("host: ")) {
+        Some(host) => Ok(host.trim().to_string()),
+        None => bail!("unable to find the host triple of rustc"),
+    }
+}
+
+/// Wait for the child process to exit, killing it after `timeout`.
+/// Return `None` if it has been killed.
+pub fn wait_timeout(child: &mut Child, timeout: Duration) -> Result<Option<ExitStatus>, Error> {
+    let start = Instant::now();
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(Some(status));
+        }
+        if start.elapsed() > timeout {
+            child.kill()?;
+            child.wait()?;
+            return Ok(None);
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+}
+
+/// SHA-1 of the content, used to name files by their content like
+/// libFuzzer does for its corpus
+pub fn hash<T: AsRef<[u8]> + ?Sized>(value: &T) -> String {
+    sha1_smol::Sha1::from(value).digest().to_string()
+}
+
+/// Copy the inputs of the corpus `from` missing from the corpus `to`,
+/// named by the hash of their content. Return the number of inputs copied.
+pub fn sync_inputs(from: &Path, to: &Path) -> Result<usize, Error> {
+    fs::create_dir_all(to).context(format!("unable to create {} dir", to.display()))?;
+
+    let mut synced = 0;
+    for entry in fs::read_dir(from).context(format!("error reading {}", from.display()))? {
+        let path = entry?.path();
+        if !path.is_file() {
+            continue;
+        }
+        let data = fs::read(&path)?;
+        let dest = to.join(hash(&data));
+        if !dest.exists() {
+            fs::write(dest, &data)?;
+            synced += 1;
+        }
+    }
+    Ok(synced)
+}
@@ -3,22 +3,29 @@ BINARYEN (Rust binding using FFI)
 - https://github.com/pepyakin/binaryen-rs
 ************************************************/
 
+use crate::outcome::{Outcome, Stage};
+
 /// Fuzzing `binaryen::Module` read
 ///
 /// NOTE: We are fuzzing binaryen over FFI.
-/// TODO: Verify that binary contains coverage for C++ code of binaryen.
-pub fn fuzz_binaryen_ffi(data: &[u8]) -> bool {
+/// TODO: Verify that binary contains coverage for C++ code of binaryen
+/// (e.g. with `warf coverage` and clang instrumentation flags).
+pub fn fuzz_binaryen_ffi(data: &[u8]) -> Outcome {
     use binaryen::Module;
 
-    Module::read(&data).is_ok()
+    // NOTE: binaryen doesn't give any reason for the failure.
+    match Module::read(&data) {
+        Ok(_) => Outcome::Accepted,
+        Err(_) => Outcome::rejected(Stage::Parse, "Module::read failed"),
+    }
 }
 
-pub fn fuzz_binaryen_optimize_ffi(data: &[u8]) -> bool {
+pub fn fuzz_binaryen_optimize_ffi(data: &[u8]) -> Outcome {
     use binaryen::{CodegenConfig, Module};
 
     let mut module = match Module::read(&data) {
         Ok(o) => o,
-        Err(_) => return false,
+        Err(_) => return Outcome::rejected(Stage::Parse, "Module::read failed"),
     };
     let config = CodegenConfig {
         optimization_level: 4,
@@ -26,5 +33,46 @@ pub fn fuzz_binaryen_optimize_ffi(data: &[u8]) -> bool {
         debug_info: true,
     };
     module.optimize(&config);
-    true
+    Outcome::Accepted
+}
+
+/// Fuzzing binaryen writer: read the module, write it back to binary
+/// then check that the output can be read again and is still valid.
+///
+/// NOTE: the output is also validated by wasmparser when the input is.
+pub fn fuzz_binaryen_roundtrip_ffi(data: &[u8]) -> Outcome {
+    use crate::wasmparser::fuzz_wasmparser_validate_all_feat;
+    use binaryen::Module;
+
+    let module = match Module::read(&data) {
+        Ok(o) => o,
+        Err(_) => return Outcome::rejected(Stage::Parse, "Module::read failed"),
+    };
+    // binaryen reads some invalid modules, writing them is not a bug
+    if !module.is_valid() {
+        return Outcome::rejected(Stage::Validate, "Module::is_valid failed");
+    }
+    let output = module.write();
+
+    let reread = match Module::read(&output) {
+        Ok(o) => o,
+        Err(_) => {
+            return Outcome::Disagreement {
+                report: "written module can't be read again".to_string(),
+            }
+        }
+    };
+    if !reread.is_valid() {
+        return Outcome::Disagreement {
+            report: "written module is not valid".to_string(),
+        };
+    }
+    if fuzz_wasmparser_validate_all_feat(&data).is_accepted() {
+        if let Outcome::Rejected { message, .. } = fuzz_wasmparser_validate_all_feat(&output) {
+            return Outcome::Disagreement {
+                report: format!("written module rejected by wasmparser: {}", message),
+            };
+        }
+    }
+    Outcome::Accepted
 }
@@ -0,0 +1,278 @@
+/***********************************************
+DUMMY IMPORTS (shared by instantiation harnesses)
+
+Walk the import section of the module and create a matching
+stub for every import: functions returning zeros, zeroed globals
+and memories/tables with the requested limits.
+************************************************/
+
+/// Dummy imports for `wasmi`.
+///
+/// Every imported function becomes a host function whose index
+/// points into `results`, allowing `DummyExternals` to return a
+/// zero value of the expected type when it is called.
+pub mod wasmi {
+    use std::cell::{Cell, RefCell};
+    use std::fmt;
+    use wasmi::memory_units::Pages;
+    use wasmi::{
+        Error, Externals, FuncInstance, FuncRef, GlobalDescriptor, GlobalInstance, GlobalRef,
+        HostError, ImportResolver, MemoryDescriptor, MemoryInstance, MemoryRef, RuntimeArgs,
+        RuntimeValue, Signature, TableDescriptor, TableInstance, TableRef, Trap, TrapKind,
+        ValueType,
+    };
+
+    /// Module name of the gas function imported by `pwasm_utils::inject_gas_counter`.
+    pub const GAS_MODULE: &str = "warf";
+
+    /// `ImportResolver` accepting any import.
+    #[derive(Default)]
+    pub struct DummyResolver {
+        results: RefCell<Vec<Option<ValueType>>>,
+        /// Fuel of the instance, `None` if the module is not metered.
+        fuel: Option<u64>,
+        gas_index: Cell<Option<usize>>,
+    }
+
+    impl DummyResolver {
+        /// Resolver of a module metered with `inject_gas_counter`,
+        /// the gas function consuming `fuel`.
+        pub fn with_fuel(fuel: u64) -> DummyResolver {
+            DummyResolver {
+                fuel: Some(fuel),
+                ..Default::default()
+            }
+        }
+
+        /// `Externals` to use when invoking functions of the instance.
+        pub fn externals(&self) -> DummyExternals {
+            DummyExternals {
+                results: self.results.borrow().clone(),
+                fuel: self.fuel,
+                gas_index: self.gas_index.get(),
+            }
+        }
+    }
+
+    impl ImportResolver for DummyResolver {
+        fn resolve_func(
+            &self,
+            module_name: &str,
+            field_name: &str,
+            signature: &Signature,
+        ) -> Result<FuncRef, Error> {
+            let mut results = self.results.borrow_mut();
+            if self.fuel.is_some() && module_name == GAS_MODULE && field_name == "gas" {
+                self.gas_index.set(Some(results.len()));
+            }
+            results.push(signature.return_type());
+            Ok(FuncInstance::alloc_host(
+                signature.clone(),
+                results.len() - 1,
+            ))
+        }
+
+        fn resolve_global(
+            &self,
+            _module_name: &str,
+            _field_name: &str,
+            descriptor: &GlobalDescriptor,
+        ) -> Result<GlobalRef, Error> {
+            Ok(GlobalInstance::alloc(
+                RuntimeValue::default(descriptor.value_type()),
+                descriptor.is_mutable(),
+            ))
+        }
+
+        fn resolve_memory(
+            &self,
+            _module_name: &str,
+            _field_name: &str,
+            descriptor: &MemoryDescriptor,
+        ) -> Result<MemoryRef, Error> {
+            MemoryInstance::alloc(
+                Pages(descriptor.initial() as usize),
+                descriptor.maximum().map(|max| Pages(max as usize)),
+            )
+        }
+
+        fn resolve_table(
+            &self,
+            _module_name: &str,
+            _field_name: &str,
+            descriptor: &TableDescriptor,
+        ) -> Result<TableRef, Error> {
+            TableInstance::alloc(descriptor.initial(), descriptor.maximum())
+        }
+    }
+
+    /// `Externals` returning a zero value for every imported function.
+    pub struct DummyExternals {
+        results: Vec<Option<ValueType>>,
+        fuel: Option<u64>,
+        gas_index: Option<usize>,
+    }
+
+    impl Externals for DummyExternals {
+        fn invoke_index(
+            &mut self,
+            index: usize,
+            args: RuntimeArgs,
+        ) -> Result<Option<RuntimeValue>, Trap> {
+            if Some(index) == self.gas_index {
+                let gas: u32 = args.nth_checked(0)?;
+                let fuel = self.fuel.unwrap_or_default();
+                if u64::from(gas) > fuel {
+                    return Err(Trap::new(TrapKind::Host(Box::new(OutOfFuel))));
+                }
+                self.fuel = Some(fuel - u64::from(gas));
+                return Ok(None);
+            }
+            Ok(self.results[index].map(RuntimeValue::default))
+        }
+    }
+
+    /// Host error raised when the gas function runs out of fuel.
+    #[derive(Debug)]
+    pub struct OutOfFuel;
+
+    impl fmt::Display for OutOfFuel {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "all fuel consumed")
+        }
+    }
+
+    impl HostError for OutOfFuel {}
+}
+
+/// Dummy imports for `wasmtime`.
+pub mod wasmtime {
+    use wasmtime::{
+        Extern, ExternType, Func, FuncType, Global, GlobalType, Memory, MemoryType, Module, Store,
+        Table, TableType, Trap, Val, ValType,
+    };
+
+    /// Create one `Extern` per import of `module`, in import order.
+    ///
+    /// Return `None` if one of the imports can't be created
+    /// (e.g. module linking imports).
+    pub fn dummy_imports(store: &Store, module: &Module) -> Option<Vec<Extern>> {
+        let mut imports = Vec::new();
+        for import in module.imports() {
+            let import = match import.ty() {
+                ExternType::Func(ty) => Extern::Func(dummy_func(store, ty)),
+                ExternType::Global(ty) => Extern::Global(dummy_global(store, ty)?),
+                ExternType::Table(ty) => Extern::Table(dummy_table(store, ty)?),
+                ExternType::Memory(ty) => Extern::Memory(dummy_memory(store, ty)),
+                _ => return None,
+            };
+            imports.push(import);
+        }
+        Some(imports)
+    }
+
+    /// Function returning zero values.
+    fn dummy_func(store: &Store, ty: FuncType) -> Func {
+        let results: Vec<ValType> = ty.results().to_vec();
+        Func::new(store, ty, move |_, _, out| {
+            for (ty, out) in results.iter().zip(out) {
+                *out = dummy_value(ty).ok_or_else(|| Trap::new("unsupported dummy result"))?;
+            }
+            Ok(())
+        })
+    }
+
+    fn dummy_global(store: &Store, ty: GlobalType) -> Option<Global> {
+        let value = dummy_value(ty.content())?;
+        Global::new(store, ty, value).ok()
+    }
+
+    fn dummy_table(store: &Store, ty: TableType) -> Option<Table> {
+        let init = dummy_value(ty.element())?;
+        Table::new(store, ty, init).ok()
+    }
+
+    fn dummy_memory(store: &Store, ty: MemoryType) -> Memory {
+        Memory::new(store, ty)
+    }
+
+    /// Zero value for `ty`.
+    fn dummy_value(ty: &ValType) -> Option<Val> {
+        Some(match ty {
+            ValType::I32 => Val::I32(0),
+            ValType::I64 => Val::I64(0),
+            ValType::F32 => Val::F32(0),
+            ValType::F64 => Val::F64(0),
+            ValType::V128 => Val::V128(0),
+            ValType::ExternRef => Val::ExternRef(None),
+            ValType::FuncRef => Val::FuncRef(None),
+        })
+    }
+}
+
+/// Dummy imports for `wasmer`.
+pub mod wasmer {
+    use std::collections::HashMap;
+    use std::sync::Arc;
+    use wasmer_runtime::Module;
+    use wasmer_runtime_core::global::Global;
+    use wasmer_runtime_core::import::{ImportObject, Namespace};
+    use wasmer_runtime_core::memory::Memory;
+    use wasmer_runtime_core::module::ImportType;
+    use wasmer_runtime_core::table::Table;
+    use wasmer_runtime_core::typed_func::DynamicFunc;
+    use wasmer_runtime_core::types::{FuncSig, Type, Value};
+
+    /// Create an `ImportObject` containing one stub per import of `module`.
+    ///
+    /// Return `None` if one of the imports can't be created.
+    pub fn dummy_imports(module: &Module) -> Option<ImportObject> {
+        let mut namespaces: HashMap<String, Namespace> = HashMap::new();
+        for import in module.imports() {
+            let namespace = namespaces
+                .entry(import.namespace.clone())
+                .or_insert_with(Namespace::new);
+            match import.ty {
+                ImportType::Function { params, results } => {
+                    let signature = Arc::new(FuncSig::new(params, results.clone()));
+                    let func = DynamicFunc::new(signature, move |_, _| {
+                        results.iter().map(|ty| dummy_value(*ty)).collect()
+                    });
+                    namespace.insert(import.name, func);
+                }
+                ImportType::Global(desc) => {
+                    let value = dummy_value(desc.ty);
+                    let global = if desc.mutable {
+                        Global::new_mutable(value)
+                    } else {
+                        Global::new(value)
+                    };
+                    namespace.insert(import.name, global);
+                }
+                ImportType::Memory(desc) => {
+                    namespace.insert(import.name, Memory::new(desc).ok()?);
+                }
+                ImportType::Table(desc) => {
+                    namespace.insert(import.name, Table::new(desc).ok()?);
+                }
+            }
+        }
+
+        let mut import_object = ImportObject::new();
+        for (name, namespace) in namespaces {
+            import_object.register(name, namespace);
+        }
+        Some(import_object)
+    }
+
+    /// Zero value for `ty`.
+    fn dummy_value(ty: Type) -> Value {
+        match ty {
+            Type::I32 => Value::I32(0),
+            Type::I64 => Value::I64(0),
+            Type::F32 => Value::F32(0.0),
+            Type::F64 => Value::F64(0.0),
+            Type::V128 => Value::V128(0),
+        }
+    }
+}
@@ -0,0 +1,484 @@
+/***********************************************
+EXECUTION (helpers shared by execution harnesses)
+************************************************/
+
+use crate::outcome::Outcome;
+
+/// Type of a function parameter, independent of the runtime.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ValType {
+    I32,
+    I64,
+    F32,
+    F64,
+}
+
+/// Value passed to an exported function, independent of the runtime.
+///
+/// NOTE: floats are stored as raw bits to keep NaN payloads intact.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Value {
+    I32(i32),
+    I64(i64),
+    F32(u32),
+    F64(u64),
+}
+
+impl Value {
+    pub fn ty(&self) -> ValType {
+        match self {
+            Value::I32(_) => ValType::I32,
+            Value::I64(_) => ValType::I64,
+            Value::F32(_) => ValType::F32,
+            Value::F64(_) => ValType::F64,
+        }
+    }
+}
+
+/// Split the fuzz input into the module and the bytes used to build the
+/// arguments of the exported functions.
+///
+/// The module ends after its last complete section (id and size), the
+/// trailing bytes are the arguments: seeds of the corpora are whole
+/// modules without arguments, and the fuzzers can append some.
+/// Inputs that are not wasm binaries are returned whole as the module.
+pub fn split_input(data: &[u8]) -> (&[u8], &[u8]) {
+    const HEADER: &[u8] = b"\0asm";
+    // Last section known by wasmparser: the tag section of the
+    // exception-handling proposal
+    const MAX_SECTION_ID: u8 = 13;
+
+    if data.len() < 8 || &data[..4] != HEADER {
+        return (data, &[]);
+    }
+    let mut end = 8;
+    while end < data.len() && data[end] <= MAX_SECTION_ID {
+        // Section size, as LEB128 u32
+        let mut size = 0u64;
+        let mut pos = end + 1;
+        let mut complete = false;
+        for shift in 0..5 {
+            let byte = match data.get(pos) {
+                Some(byte) => *byte,
+                None => break,
+            };
+            pos += 1;
+            size |= u64::from(byte & 0x7f) << (7 * shift);
+            if byte & 0x80 == 0 {
+                complete = true;
+                break;
+            }
+        }
+        if !complete || pos as u64 + size > data.len() as u64 {
+            break;
+        }
+        end = pos + size as usize;
+    }
+    data.split_at(end)
+}
+
+/// Build function arguments from the bytes following the module
+/// (see `split_input`).
+///
+/// Bytes are consumed sequentially, every call starting where the
+/// previous one stopped, and the reader wraps around once all the
+/// input has been used. An empty input only produces zeros.
+pub struct ArgsReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> ArgsReader<'a> {
+    pub fn new(data: &'a [u8]) -> ArgsReader<'a> {
+        ArgsReader { data, pos: 0 }
+    }
+
+    /// Read `len` bytes as a little-endian integer.
+    fn read(&mut self, len: usize) -> u64 {
+        let mut value = 0u64;
+        if self.data.is_empty() {
+            return value;
+        }
+        for i in 0..len {
+            let byte = self.data[self.pos % self.data.len()];
+            value |= u64::from(byte) << (8 * i);
+            self.pos = (self.pos + 1) % self.data.len();
+        }
+        value
+    }
+
+    /// Create one argument of the given type.
+    pub fn value(&mut self, ty: ValType) -> Value {
+        match ty {
+            ValType::I32 => Value::I32(self.read(4) as i32),
+            ValType::I64 => Value::I64(self.read(8) as i64),
+            ValType::F32 => Value::F32(self.read(4) as u32),
+            ValType::F64 => Value::F64(self.read(8)),
+        }
+    }
+
+    /// Create the arguments for a function taking `params`.
+    pub fn args(&mut self, params: &[ValType]) -> Vec<Value> {
+        params.iter().map(|ty| self.value(*ty)).collect()
+    }
+}
+
+/// Fuel given to an execution when `WARF_FUEL` is not set.
+pub const DEFAULT_FUEL: u64 = 100_000;
+
+/// Amount of fuel, i.e. roughly the number of wasm instructions, that
+/// an execution harness can consume for the start function and all
+/// the calls of exported functions.
+///
+/// NOTE: set by warf with the `--fuel` option through `WARF_FUEL`,
+/// so that infinite loops end with `TrapKind::OutOfFuel` instead
+/// of a fuzzer timeout.
+pub fn fuel_limit() -> u64 {
+    std::env::var("WARF_FUEL")
+        .ok()
+        .and_then(|fuel| fuel.parse().ok())
+        .unwrap_or(DEFAULT_FUEL)
+}
+
+/// Whether the module has an import section with at least one entry.
+pub fn has_imports(data: &[u8]) -> bool {
+    use wasmparser::{Parser, Payload};
+
+    Parser::new(0)
+        .parse_all(&data)
+        .any(|payload| match payload {
+            Ok(Payload::ImportSection(reader)) => reader.get_count() > 0,
+            _ => false,
+        })
+}
+
+/// Names of the exported functions of a module, in export section order.
+///
+/// NOTE: all the runtimes iterate over this list so that every exported
+/// function receives the same arguments whatever the runtime.
+pub fn exported_functions(data: &[u8]) -> Vec<String> {
+    use wasmparser::{ExternalKind, Parser, Payload};
+
+    let mut names = Vec::new();
+    for payload in Parser::new(0).parse_all(&data) {
+        if let Ok(Payload::ExportSection(reader)) = payload {
+            for export in reader {
+                match export {
+                    Ok(export) if export.kind == ExternalKind::Function => {
+                        names.push(export.field.to_string())
+                    }
+                    Ok(_) => (),
+                    Err(_) => break,
+                }
+            }
+        }
+    }
+    names
+}
+
+/// Parameters and results of a function.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Signature {
+    pub params: Vec<ValType>,
+    pub results: Vec<ValType>,
+}
+
+/// Exported functions of a module with their signature, in export
+/// section order.
+///
+/// NOTE: for runtimes whose API needs the signature at compile time
+/// (e.g. wasm3), functions using other types than `ValType` are skipped.
+pub fn exported_signatures(data: &[u8]) -> Vec<(String, Signature)> {
+    use wasmparser::{ExternalKind, ImportSectionEntryType, Parser, Payload, Type, TypeDef};
+
+    let to_val_type = |ty: &Type| match ty {
+        Type::I32 => Some(ValType::I32),
+        Type::I64 => Some(ValType::I64),
+        Type::F32 => Some(ValType::F32),
+        Type::F64 => Some(ValType::F64),
+        _ => None,
+    };
+
+    let mut types = Vec::new();
+    // Type of every function, imported ones first
+    let mut functions = Vec::new();
+    let mut exports = Vec::new();
+    for payload in Parser::new(0).parse_all(&data) {
+        match payload {
+            Ok(Payload::TypeSection(reader)) => {
+                for ty in reader {
+                    let signature = match ty {
+                        Ok(TypeDef::Func(ty)) => {
+                            let params: Option<Vec<_>> =
+                                ty.params.iter().map(to_val_type).collect();
+                            let results: Option<Vec<_>> =
+                                ty.returns.iter().map(to_val_type).collect();
+                            params.and_then(|params| {
+                                results.map(|results| Signature { params, results })
+                            })
+                        }
+                        Ok(_) => None,
+                        Err(_) => break,
+                    };
+                    types.push(signature);
+                }
+            }
+            Ok(Payload::ImportSection(reader)) => {
+                for import in reader {
+                    match import {
+                        Ok(import) => {
+                            if let ImportSectionEntryType::Function(index) = import.ty {
+                                functions.push(index);
+                            }
+                        }
+                        Err(_) => break,
+                    }
+                }
+            }
+            Ok(Payload::FunctionSection(reader)) => {
+                for index in reader {
+                    match index {
+                        Ok(index) => functions.push(index),
+                        Err(_) => break,
+                    }
+                }
+            }
+            Ok(Payload::ExportSection(reader)) => {
+                for export in reader {
+                    match export {
+                        Ok(export) if export.kind == ExternalKind::Function => {
+                            exports.push((export.field.to_string(), export.index))
+                        }
+                        Ok(_) => (),
+                        Err(_) => break,
+                    }
+                }
+            }
+            Ok(_) => (),
+            Err(_) => break,
+        }
+    }
+
+    exports
+        .into_iter()
+        .filter_map(|(name, index)| {
+            let ty = *functions.get(index as usize)?;
+            let signature = types.get(ty as usize)?.clone()?;
+            Some((name, signature))
+        })
+        .collect()
+}
+
+/// Trap raised during execution, normalized across runtimes.
+///
+/// NOTE: runtimes do not report traps with the same granularity
+/// (e.g. wasmer merges all arithmetic traps), so close kinds are
+/// grouped together.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TrapKind {
+    Unreachable,
+    MemoryOutOfBounds,
+    TableOutOfBounds,
+    BadSignature,
+    Arithmetic,
+    StackOverflow,
+    /// The execution used all the fuel given by `fuel_limit`.
+    OutOfFuel,
+    /// Trap not recognized, with the message of the runtime.
+    Unknown(String),
+}
+
+impl TrapKind {
+    /// Classify a trap using the message reported by the runtime.
+    ///
+    /// NOTE: only the first line is used, the following ones may
+    /// contain a backtrace with arbitrary function names.
+    pub fn from_message(message: &str) -> TrapKind {
+        let message = message.lines().next().unwrap_or_default();
+        let msg = message.to_lowercase();
+        if msg.contains("unreachable") {
+            TrapKind::Unreachable
+        } else if msg.contains("out of bounds memory") || msg.contains("memory out-of-bounds") {
+            TrapKind::MemoryOutOfBounds
+        } else if msg.contains("signature") || msg.contains("type mismatch") {
+            TrapKind::BadSignature
+        } else if msg.contains("element") || msg.contains("table") || msg.contains("call_indirect")
+        {
+            TrapKind::TableOutOfBounds
+        } else if msg.contains("divide by zero")
+            || msg.contains("division by zero")
+            || msg.contains("integer overflow")
+            || msg.contains("conversion to integer")
+            || msg.contains("arithmetic")
+        {
+            TrapKind::Arithmetic
+        } else if msg.contains("fuel") {
+            TrapKind::OutOfFuel
+        } else if msg.contains("stack") {
+            TrapKind::StackOverflow
+        } else {
+            TrapKind::Unknown(message.to_string())
+        }
+    }
+
+    /// Whether this trap can be compared with the one of another runtime.
+    ///
+    /// Stack limits and fuel accounting are specific to each runtime so
+    /// running out of them is not a disagreement, and unknown traps
+    /// can't be classified.
+    pub fn is_comparable(&self) -> bool {
+        match self {
+            TrapKind::StackOverflow | TrapKind::OutOfFuel | TrapKind::Unknown(_) => false,
+            _ => true,
+        }
+    }
+}
+
+/// Result of one call: the returned values or the trap.
+pub type CallResult = Result<Vec<Value>, TrapKind>;
+
+/// Call of one exported function.
+#[derive(Clone, Debug)]
+pub struct Call {
+    pub name: String,
+    pub args: Vec<Value>,
+    pub result: CallResult,
+}
+
+/// Result of the execution of a module by one runtime.
+#[derive(Clone, Debug)]
+pub enum Execution {
+    /// Module not executed (parsing, validation or linking failed),
+    /// with the outcome explaining why.
+    Rejected(Outcome),
+    /// The start function trapped.
+    StartTrapped(TrapKind),
+    /// All the exported functions have been called.
+    Executed(Vec<Call>),
+}
+
+impl Execution {
+    /// Outcome of the whole execution, a trap in an exported
+    /// function being a normal result.
+    pub fn outcome(&self) -> Outcome {
+        match self {
+            Execution::Rejected(outcome) => outcome.clone(),
+            Execution::StartTrapped(kind) => Outcome::Trapped { kind: kind.clone() },
+            Execution::Executed(_) => Outcome::Accepted,
+        }
+    }
+}
+
+/// Compare two values, any NaN being equal to any other NaN.
+///
+/// NOTE: NaN bit patterns are nondeterministic in the spec.
+fn same_value(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::F32(a), Value::F32(b)) => {
+            a == b || (f32::from_bits(*a).is_nan() && f32::from_bits(*b).is_nan())
+        }
+        (Value::F64(a), Value::F64(b)) => {
+            a == b || (f64::from_bits(*a).is_nan() && f64::from_bits(*b).is_nan())
+        }
+        _ => a == b,
+    }
+}
+
+fn same_result(a: &CallResult, b: &CallResult) -> bool {
+    match (a, b) {
+        (Ok(a), Ok(b)) => a.len() == b.len() && a.iter().zip(b).all(|(a, b)| same_value(a, b)),
+        (Err(a), Err(b)) => a == b || !a.is_comparable() || !b.is_comparable(),
+        _ => false,
+    }
+}
+
+/// Whether a call ended with a trap specific to the runtime, after
+/// which the states of the different instances can't be compared anymore.
+fn is_inconclusive(result: &CallResult) -> bool {
+    match result {
+        Err(trap) => !trap.is_comparable(),
+        Ok(_) => false,
+    }
+}
+
+/// Compare the executions of the same module by different runtimes.
+///
+/// Runtimes that rejected the module are ignored, `diff_instantiate`
+/// is in charge of those disagreements. Return a detailed report
+/// of the first disagreement found.
+pub fn diff_executions(executions: &[(&str, Execution)]) -> Option<String> {
+    use std::fmt::Write;
+
+    let executed: Vec<&(&str, Execution)> = executions
+        .iter()
+        .filter(|(_, e)| !matches!(e, Execution::Rejected(_)))
+        .collect();
+    if executed.len() < 2 {
+        return None;
+    }
+
+    // Compare the start functions
+    let starts: Vec<Option<&TrapKind>> = executed
+        .iter()
+        .map(|(_, e)| match e {
+            Execution::StartTrapped(trap) => Some(trap),
+            _ => None,
+        })
+        .collect();
+    if starts
+        .iter()
+        .any(|s| s.map_or(false, |t| !t.is_comparable()))
+    {
+        return None;
+    }
+    if starts.iter().any(|s| *s != starts[0]) {
+        let mut report = String::from("start function disagreement\n");
+        for ((runtime, _), start) in executed.iter().zip(&starts) {
+            let _ = writeln!(report, "  {}: {:?}", runtime, start);
+        }
+        return Some(report);
+    }
+
+    // Compare the calls of the exported functions
+    let calls: Vec<&Vec<Call>> = executed
+        .iter()
+        .filter_map(|(_, e)| match e {
+            Execution::Executed(calls) => Some(calls),
+            _ => None,
+        })
+        .collect();
+    if calls.len() != executed.len() {
+        return None;
+    }
+    let count = calls.iter().map(|c| c.len()).min().unwrap_or(0);
+    for i in 0..count {
+        let first = &calls[0][i];
+        let agree = calls.iter().all(|c| {
+            c[i].name == first.name
+                && c[i].args == first.args
+                && same_result(&c[i].result, &first.result)
+        });
+        if !agree {
+            let mut report = format!("function `{}` called with {:?}\n", first.name, first.args);
+            for ((runtime, _), c) in executed.iter().zip(&calls) {
+                let _ = writeln!(
+                    report,
+                    "  {}: `{}`{:?} => {:?}",
+                    runtime, c[i].name, c[i].args, c[i].result
+                );
+            }
+            return Some(report);
+        }
+        if calls.iter().any(|c| is_inconclusive(&c[i].result)) {
+            return None;
+        }
+    }
+    if calls.iter().any(|c| c.len() != calls[0].len()) {
+        let mut report = String::from("number of exported functions called disagreement\n");
+        for ((runtime, _), c) in executed.iter().zip(&calls) {
+            let _ = writeln!(report, "  {}: {}", runtime, c.len());
+        }
+        return Some(report);
+    }
+    None
+}
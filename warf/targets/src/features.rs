@@ -0,0 +1,84 @@
+/***********************************************
+FEATURES (proposal sets of differential validation)
+************************************************/
+
+/// WebAssembly proposals enabled on top of the MVP.
+///
+/// NOTE: sign-extension operators and non-trapping float-to-int
+/// conversions are part of every set, wasmparser can't disable them.
+/// Engines limited to the MVP ignore the modules using them.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Features {
+    pub simd: bool,
+    pub threads: bool,
+    pub reference_types: bool,
+    pub bulk_memory: bool,
+    pub multi_value: bool,
+}
+
+impl Features {
+    pub const MVP: Features = Features {
+        simd: false,
+        threads: false,
+        reference_types: false,
+        bulk_memory: false,
+        multi_value: false,
+    };
+    pub const SIMD: Features = Features {
+        simd: true,
+        ..Features::MVP
+    };
+    pub const THREADS: Features = Features {
+        threads: true,
+        ..Features::MVP
+    };
+    /// Reference types depend on bulk memory (e.g. `table.init`).
+    pub const REFERENCE_TYPES: Features = Features {
+        reference_types: true,
+        bulk_memory: true,
+        ..Features::MVP
+    };
+    pub const BULK_MEMORY: Features = Features {
+        bulk_memory: true,
+        ..Features::MVP
+    };
+    pub const MULTI_VALUE: Features = Features {
+        multi_value: true,
+        ..Features::MVP
+    };
+
+    pub fn is_mvp(&self) -> bool {
+        *self == Features::MVP
+    }
+}
+
+/// Whether the module uses sign-extension or non-trapping float-to-int
+/// operators. Malformed code sections are ignored.
+pub fn uses_post_mvp_operators(data: &[u8]) -> bool {
+    use wasmparser::{Operator, Parser, Payload};
+
+    Parser::new(0)
+        .parse_all(&data)
+        .filter_map(|payload| match payload {
+            Ok(Payload::CodeSectionEntry(body)) => body.get_operators_reader().ok(),
+            _ => None,
+        })
+        .any(|reader| {
+            reader.into_iter().any(|op| match op {
+                Ok(Operator::I32Extend8S)
+                | Ok(Operator::I32Extend16S)
+                | Ok(Operator::I64Extend8S)
+                | Ok(Operator::I64Extend16S)
+                | Ok(Operator::I64Extend32S)
+                | Ok(Operator::I32TruncSatF32S)
+                | Ok(Operator::I32TruncSatF32U)
+                | Ok(Operator::I32TruncSatF64S)
+                | Ok(Operator::I32TruncSatF64U)
+                | Ok(Operator::I64TruncSatF32S)
+                | Ok(Operator::I64TruncSatF32U)
+                | Ok(Operator::I64TruncSatF64S)
+                | Ok(Operator::I64TruncSatF64U) => true,
+                _ => false,
+            })
+        })
+}
@@ -3,9 +3,103 @@ fizzy
 - https://github.com/wasmx/fizzy
 ************************************************/
 
+use crate::execution::TrapKind;
+use crate::features::Features;
+use crate::outcome::{Outcome, Stage};
 use fizzy::validate;
 
-pub fn fizzy_validate(data: &[u8]) -> bool {
+pub fn fizzy_validate(data: &[u8]) -> Outcome {
     // Parse binary into syntax tree
-    validate(&data)
+    // NOTE: fizzy only reports whether the module is valid.
+    if validate(&data) {
+        Outcome::Accepted
+    } else {
+        Outcome::rejected(Stage::Validate, "validate failed")
+    }
+}
+
+/// `fizzy_validate` for the MVP, the only proposal set supported by fizzy.
+pub fn fizzy_validate_with_features(data: &[u8], features: Features) -> Outcome {
+    use crate::features::uses_post_mvp_operators;
+
+    if !features.is_mvp() || uses_post_mvp_operators(&data) {
+        return Outcome::Unsupported;
+    }
+    fizzy_validate(&data)
+}
+
+/// Parse and instantiate the module.
+///
+/// NOTE: the Rust binding of fizzy can't provide imports so modules
+/// with imports are `Unsupported`.
+/// NOTE: fizzy doesn't report the kind of trap of the start function,
+/// it is `TrapKind::Unknown`.
+fn instantiate(data: &[u8]) -> Result<fizzy::Instance, Outcome> {
+    use crate::execution::has_imports;
+    use fizzy::parse;
+
+    if has_imports(&data) {
+        return Err(Outcome::Unsupported);
+    }
+    let module = match parse(&data) {
+        Ok(module) => module,
+        Err(e) => return Err(Outcome::rejected(Stage::Parse, format!("{:?}", e))),
+    };
+    // A trap inside the start function makes the instantiation fail,
+    // only the message tells it apart from the other errors.
+    match module.instantiate() {
+        Ok(instance) => Ok(instance),
+        Err(e) => {
+            let message = format!("{:?}", e);
+            if message.contains("start function failed") {
+                Err(Outcome::Trapped {
+                    kind: TrapKind::Unknown(message),
+                })
+            } else {
+                Err(Outcome::rejected(Stage::Instantiate, message))
+            }
+        }
+    }
+}
+
+/// Fuzzing `fizzy::Module::instantiate`.
+pub fn fizzy_instantiate(data: &[u8]) -> Outcome {
+    match instantiate(&data) {
+        Ok(_) => Outcome::Accepted,
+        Err(outcome) => outcome,
+    }
+}
+
+/// Fuzzing fizzy interpreter: instantiate the module then call all
+/// exported functions with arguments built from the fuzz input.
+///
+/// NOTE: fizzy has no fuel, only a call depth limit, so infinite
+/// loops end with a fuzzer timeout.
+pub fn fizzy_execute(data: &[u8]) -> Outcome {
+    use crate::execution::{exported_signatures, split_input, ArgsReader, Value};
+    use fizzy::TypedValue;
+
+    let (data, args) = split_input(data);
+
+    let mut instance = match instantiate(&data) {
+        Ok(instance) => instance,
+        Err(outcome) => return outcome,
+    };
+
+    let mut reader = ArgsReader::new(args);
+    for (name, signature) in exported_signatures(&data) {
+        let args: Vec<TypedValue> = reader
+            .args(&signature.params)
+            .into_iter()
+            .map(|value| match value {
+                Value::I32(v) => TypedValue::U32(v as u32),
+                Value::I64(v) => TypedValue::U64(v as u64),
+                Value::F32(bits) => TypedValue::F32(f32::from_bits(bits)),
+                Value::F64(bits) => TypedValue::F64(f64::from_bits(bits)),
+            })
+            .collect();
+        // Traps are expected, only crashes are interesting
+        let _ = instance.execute(&name, &args);
+    }
+    Outcome::Accepted
 }
@@ -1,118 +1,297 @@
 /*
 Differential fuzzing:
 We are checking that all those different implementation return
-the same thing i.e. true or false in our cases.
+the same thing i.e. all accept or all reject the module, the
+`Outcome` giving the reason of each of them in the panic message.
 */
 
 pub fn fuzz_diff_parsing(data: &[u8]) {
-    let a = parity_wasm::parity_wasm_deserialize(&data);
-    let b = wasmer::fuzz_wasmer_compile_clif(&data);
-    let c = wasmer::fuzz_wasmer_compile_singlepass(&data);
-    let d = wasmtime::fuzz_wasmtime_compile_all_cranelift(&data);
-    let e = wasmparser::fuzz_wasmparser_validate_all_feat(&data);
-    let f = binaryen_ffi::fuzz_binaryen_ffi(&data);
-    let g = wasmprinter::fuzz_wasmprinter_parser(&data);
-
-    let _ = match (a, b, c, d, e, f, g) {
-        (true, true, true, true, true, true, true) => true,
-        (false, false, false, false, false, false, false) => false,
-        _ => panic!(
-            "fuzz_diff_parsing panic: {}-{}-{}-{}-{}-{}-{}",
-            a, b, c, d, e, f, g
-        ),
-    };
-}
-
-pub fn debug_diff_parsing(data: &[u8]) -> bool {
-    let a = parity_wasm::parity_wasm_deserialize(&data);
-    let b = wasmer::fuzz_wasmer_compile_clif(&data);
-    let c = wasmer::fuzz_wasmer_compile_singlepass(&data);
-    let d = wasmtime::fuzz_wasmtime_compile_all_cranelift(&data);
-    let e = wasmparser::fuzz_wasmparser_validate_all_feat(&data);
-    let f = binaryen_ffi::fuzz_binaryen_ffi(&data);
-    let g = wasmprinter::fuzz_wasmprinter_parser(&data);
-
-    match (a, b, c, d, e, f, g) {
-        (true, true, true, true, true, true, true) => true,
-        (false, false, false, false, false, false, false) => true,
-        _ => false,
+    if let Outcome::Disagreement { report } = debug_diff_parsing(&data) {
+        panic!("fuzz_diff_parsing panic:\n{}", report);
     }
 }
 
+pub fn debug_diff_parsing(data: &[u8]) -> Outcome {
+    diff_outcomes(&[
+        ("parity_wasm", parity_wasm::parity_wasm_deserialize(&data)),
+        ("wasmer_clif", wasmer::fuzz_wasmer_compile_clif(&data)),
+        (
+            "wasmer_singlepass",
+            wasmer::fuzz_wasmer_compile_singlepass(&data),
+        ),
+        (
+            "wasmtime",
+            wasmtime::fuzz_wasmtime_compile_all_cranelift(&data),
+        ),
+        (
+            "wasmparser",
+            wasmparser::fuzz_wasmparser_validate_all_feat(&data),
+        ),
+        ("binaryen", binaryen_ffi::fuzz_binaryen_ffi(&data)),
+        ("wasmprinter", wasmprinter::fuzz_wasmprinter_parser(&data)),
+    ])
+}
+
 pub fn fuzz_diff_all_validate(data: &[u8]) {
-    let a = wasmi::wasmi_validate(&data);
-    let b = wasmer::fuzz_wasmer_validate(&data);
-    let c = wasmtime::fuzz_wasmtime_validate_all_feat(&data);
-    let d = wasmparser::fuzz_wasmparser_validate_all_feat(&data);
-    let e = wabt_ffi::fuzz_wabt_validate_ffi(&data);
-    let f = fizzy::fizzy_validate(&data);
-
-    let _ = match (a, b, c, d, e, f) {
-        (true, true, true, true, true, true) => true,
-        (false, false, false, false, false, false) => false,
-        _ => panic!(
-            "fuzz_diff_all_validate panic: {}-{}-{}-{}-{}-{}",
-            a, b, c, d, e, f
+    if let Outcome::Disagreement { report } = debug_diff_all_validate(&data) {
+        panic!("fuzz_diff_all_validate panic:\n{}", report);
+    }
+}
+
+pub fn debug_diff_all_validate(data: &[u8]) -> Outcome {
+    diff_outcomes(&[
+        ("wasmi", wasmi::wasmi_validate(&data)),
+        ("wasmer", wasmer::fuzz_wasmer_validate(&data)),
+        ("wasmtime", wasmtime::fuzz_wasmtime_validate_all_feat(&data)),
+        (
+            "wasmparser",
+            wasmparser::fuzz_wasmparser_validate_all_feat(&data),
+        ),
+        ("wabt", wabt_ffi::fuzz_wabt_validate_ffi(&data)),
+        ("fizzy", fizzy::fizzy_validate(&data)),
+    ])
+}
+
+/*
+Differential validation per proposal set:
+All the engines are configured with the same proposals, so that
+they only disagree on bugs and not on their default features.
+Engines that can't be configured with a set return `Unsupported`
+and are ignored.
+*/
+
+fn diff_validate_with_features(data: &[u8], features: Features) -> Outcome {
+    diff_outcomes(&[
+        (
+            "wasmi",
+            wasmi::wasmi_validate_with_features(&data, features),
+        ),
+        (
+            "wasmer",
+            wasmer::fuzz_wasmer_validate_with_features(&data, features),
+        ),
+        (
+            "wasmtime",
+            wasmtime::fuzz_wasmtime_validate_with_features(&data, features),
         ),
-    };
+        (
+            "wasmparser",
+            wasmparser::fuzz_wasmparser_validate_with_features(&data, features),
+        ),
+        (
+            "wabt",
+            wabt_ffi::fuzz_wabt_validate_with_features_ffi(&data, features),
+        ),
+        (
+            "fizzy",
+            fizzy::fizzy_validate_with_features(&data, features),
+        ),
+    ])
+}
+
+pub fn fuzz_diff_validate_mvp(data: &[u8]) {
+    if let Outcome::Disagreement { report } = debug_diff_validate_mvp(&data) {
+        panic!("fuzz_diff_validate_mvp panic:\n{}", report);
+    }
+}
+
+pub fn debug_diff_validate_mvp(data: &[u8]) -> Outcome {
+    diff_validate_with_features(&data, Features::MVP)
+}
+
+pub fn fuzz_diff_validate_simd(data: &[u8]) {
+    if let Outcome::Disagreement { report } = debug_diff_validate_simd(&data) {
+        panic!("fuzz_diff_validate_simd panic:\n{}", report);
+    }
+}
+
+pub fn debug_diff_validate_simd(data: &[u8]) -> Outcome {
+    diff_validate_with_features(&data, Features::SIMD)
+}
+
+pub fn fuzz_diff_validate_threads(data: &[u8]) {
+    if let Outcome::Disagreement { report } = debug_diff_validate_threads(&data) {
+        panic!("fuzz_diff_validate_threads panic:\n{}", report);
+    }
 }
 
-pub fn debug_diff_all_validate(data: &[u8]) -> bool {
-    let a = wasmi::wasmi_validate(&data);
-    let b = wasmer::fuzz_wasmer_validate(&data);
-    let c = wasmtime::fuzz_wasmtime_validate_all_feat(&data);
-    let d = wasmparser::fuzz_wasmparser_validate_all_feat(&data);
-    let e = wabt_ffi::fuzz_wabt_validate_ffi(&data);
-    let f = fizzy::fizzy_validate(&data);
+pub fn debug_diff_validate_threads(data: &[u8]) -> Outcome {
+    diff_validate_with_features(&data, Features::THREADS)
+}
 
-    match (a, b, c, d, e, f) {
-        (true, true, true, true, true, true) => true,
-        (false, false, false, false, false, false) => true,
-        _ => false,
+pub fn fuzz_diff_validate_reference_types(data: &[u8]) {
+    if let Outcome::Disagreement { report } = debug_diff_validate_reference_types(&data) {
+        panic!("fuzz_diff_validate_reference_types panic:\n{}", report);
     }
 }
 
+pub fn debug_diff_validate_reference_types(data: &[u8]) -> Outcome {
+    diff_validate_with_features(&data, Features::REFERENCE_TYPES)
+}
+
+pub fn fuzz_diff_validate_bulk_memory(data: &[u8]) {
+    if let Outcome::Disagreement { report } = debug_diff_validate_bulk_memory(&data) {
+        panic!("fuzz_diff_validate_bulk_memory panic:\n{}", report);
+    }
+}
+
+pub fn debug_diff_validate_bulk_memory(data: &[u8]) -> Outcome {
+    diff_validate_with_features(&data, Features::BULK_MEMORY)
+}
+
+pub fn fuzz_diff_validate_multi_value(data: &[u8]) {
+    if let Outcome::Disagreement { report } = debug_diff_validate_multi_value(&data) {
+        panic!("fuzz_diff_validate_multi_value panic:\n{}", report);
+    }
+}
+
+pub fn debug_diff_validate_multi_value(data: &[u8]) -> Outcome {
+    diff_validate_with_features(&data, Features::MULTI_VALUE)
+}
+
 pub fn fuzz_diff_instantiate(data: &[u8]) {
-    let a = wasmi::wasmi_instantiate(&data);
-    let b = wasmer::fuzz_wasmer_instantiate(&data);
-    let c = wasmtime::fuzz_wasmtime_instantiate_all_cranelift(&data);
-    let _ = match (a, b, c) {
-        (true, true, true) => true,
-        (false, false, false) => false,
-        _ => panic!("fuzz_diff_instantiate panic: {}-{}-{}", a, b, c),
-    };
-}
-
-pub fn debug_diff_instantiate(data: &[u8]) -> bool {
-    let a = wasmi::wasmi_instantiate(&data);
-    let b = wasmer::fuzz_wasmer_instantiate(&data);
-    let c = wasmtime::fuzz_wasmtime_instantiate_all_cranelift(&data);
-    match (a, b, c) {
-        (true, true, true) => true,
-        (false, false, false) => true,
-        _ => false,
+    if let Outcome::Disagreement { report } = debug_diff_instantiate(&data) {
+        panic!("fuzz_diff_instantiate panic:\n{}", report);
+    }
+}
+
+pub fn debug_diff_instantiate(data: &[u8]) -> Outcome {
+    diff_outcomes(&[
+        ("wasmi", wasmi::wasmi_instantiate(&data)),
+        ("wasmer", wasmer::fuzz_wasmer_instantiate(&data)),
+        (
+            "wasmtime",
+            wasmtime::fuzz_wasmtime_instantiate_all_cranelift(&data),
+        ),
+        ("fizzy", fizzy::fizzy_instantiate(&data)),
+    ])
+}
+
+pub fn fuzz_diff_execute(data: &[u8]) {
+    if let Outcome::Disagreement { report } = debug_diff_execute(&data) {
+        panic!("fuzz_diff_execute panic:\n{}", report);
+    }
+}
+
+pub fn debug_diff_execute(data: &[u8]) -> Outcome {
+    let executions = [
+        ("wasmi", wasmi::wasmi_execute_exports(&data)),
+        ("wasmer", wasmer::wasmer_execute_exports(&data)),
+        ("wasmtime", wasmtime::wasmtime_execute_exports(&data)),
+    ];
+    match execution::diff_executions(&executions) {
+        Some(report) => Outcome::Disagreement { report },
+        None => Outcome::Accepted,
     }
 }
 
 pub fn fuzz_diff_wat_parsing(data: &[u8]) {
-    let a = wabt_ffi::fuzz_wabt_wat2wasm_ffi(&data);
-    let b = wat::wat_parser(&data);
-    let _ = match (a, b) {
-        (true, true) => true,
-        (false, false) => false,
-        _ => panic!("fuzz_diff_wat_parsing panic: {}-{}", a, b),
-    };
-}
-
-pub fn debug_diff_wat_parsing(data: &[u8]) -> bool {
-    let a = wabt_ffi::fuzz_wabt_wat2wasm_ffi(&data);
-    let b = wat::wat_parser(&data);
-    match (a, b) {
-        (true, true) => true,
-        (false, false) => true,
-        _ => false,
+    if let Outcome::Disagreement { report } = debug_diff_wat_parsing(&data) {
+        panic!("fuzz_diff_wat_parsing panic:\n{}", report);
+    }
+}
+
+pub fn debug_diff_wat_parsing(data: &[u8]) -> Outcome {
+    diff_outcomes(&[
+        ("wabt", wabt_ffi::fuzz_wabt_wat2wasm_ffi(&data)),
+        ("wat", wat::wat_parser(&data)),
+    ])
+}
+
+/*
+Structure-aware fuzzing:
+The fuzz input is turned into an always-valid module by wasm-smith
+so that compilers and instantiation are reached on every run.
+*/
+
+mod smith;
+use smith::with_smith;
+
+// fuzzing harnesses
+pub fn fuzz_wasmi_instantiate_smith(data: &[u8]) {
+    let _ = with_smith(&data, wasmi::wasmi_instantiate);
+}
+pub fn fuzz_wasmer_compile_clif_smith(data: &[u8]) {
+    let _ = with_smith(&data, wasmer::fuzz_wasmer_compile_clif);
+}
+pub fn fuzz_wasmer_compile_singlepass_smith(data: &[u8]) {
+    let _ = with_smith(&data, wasmer::fuzz_wasmer_compile_singlepass);
+}
+pub fn fuzz_wasmer_instantiate_smith(data: &[u8]) {
+    let _ = with_smith(&data, wasmer::fuzz_wasmer_instantiate);
+}
+pub fn fuzz_wasmtime_compile_smith(data: &[u8]) {
+    let _ = with_smith(&data, wasmtime::fuzz_wasmtime_compile);
+}
+pub fn fuzz_wasmtime_compile_all_cranelift_smith(data: &[u8]) {
+    let _ = with_smith(&data, wasmtime::fuzz_wasmtime_compile_all_cranelift);
+}
+pub fn fuzz_wasmtime_instantiate_all_cranelift_smith(data: &[u8]) {
+    let _ = with_smith(&data, wasmtime::fuzz_wasmtime_instantiate_all_cranelift);
+}
+pub fn fuzz_diff_parsing_smith(data: &[u8]) {
+    if let Outcome::Disagreement { report } = debug_diff_parsing_smith(&data) {
+        panic!("fuzz_diff_parsing_smith panic:\n{}", report);
+    }
+}
+pub fn fuzz_diff_all_validate_smith(data: &[u8]) {
+    if let Outcome::Disagreement { report } = debug_diff_all_validate_smith(&data) {
+        panic!("fuzz_diff_all_validate_smith panic:\n{}", report);
     }
 }
+pub fn fuzz_diff_instantiate_smith(data: &[u8]) {
+    if let Outcome::Disagreement { report } = debug_diff_instantiate_smith(&data) {
+        panic!("fuzz_diff_instantiate_smith panic:\n{}", report);
+    }
+}
+pub fn fuzz_diff_execute_smith(data: &[u8]) {
+    if let Outcome::Disagreement { report } = debug_diff_execute_smith(&data) {
+        panic!("fuzz_diff_execute_smith panic:\n{}", report);
+    }
+}
+// debug target
+pub fn debug_wasmi_instantiate_smith(data: &[u8]) -> Outcome {
+    with_smith(&data, wasmi::wasmi_instantiate)
+}
+pub fn debug_wasmer_compile_clif_smith(data: &[u8]) -> Outcome {
+    with_smith(&data, wasmer::fuzz_wasmer_compile_clif)
+}
+pub fn debug_wasmer_compile_singlepass_smith(data: &[u8]) -> Outcome {
+    with_smith(&data, wasmer::fuzz_wasmer_compile_singlepass)
+}
+pub fn debug_wasmer_instantiate_smith(data: &[u8]) -> Outcome {
+    with_smith(&data, wasmer::fuzz_wasmer_instantiate)
+}
+pub fn debug_wasmtime_compile_smith(data: &[u8]) -> Outcome {
+    with_smith(&data, wasmtime::fuzz_wasmtime_compile)
+}
+pub fn debug_wasmtime_compile_all_cranelift_smith(data: &[u8]) -> Outcome {
+    with_smith(&data, wasmtime::fuzz_wasmtime_compile_all_cranelift)
+}
+pub fn debug_wasmtime_instantiate_all_cranelift_smith(data: &[u8]) -> Outcome {
+    with_smith(&data, wasmtime::fuzz_wasmtime_instantiate_all_cranelift)
+}
+pub fn debug_diff_parsing_smith(data: &[u8]) -> Outcome {
+    with_smith(&data, debug_diff_parsing)
+}
+pub fn debug_diff_all_validate_smith(data: &[u8]) -> Outcome {
+    with_smith(&data, debug_diff_all_validate)
+}
+pub fn debug_diff_instantiate_smith(data: &[u8]) -> Outcome {
+    with_smith(&data, debug_diff_instantiate)
+}
+pub fn debug_diff_execute_smith(data: &[u8]) -> Outcome {
+    with_smith(&data, debug_diff_execute)
+}
+
+mod dummy;
+mod execution;
+mod features;
+mod outcome;
+pub use execution::TrapKind;
+use features::Features;
+use outcome::diff_outcomes;
+pub use outcome::{Outcome, Stage};
 
 mod wasmi;
 // fuzzing harnesses
@@ -122,23 +301,39 @@ pub fn fuzz_wasmi_validate(data: &[u8]) {
 pub fn fuzz_wasmi_instantiate(data: &[u8]) {
     let _ = wasmi::wasmi_instantiate(&data);
 }
+pub fn fuzz_wasmi_execute(data: &[u8]) {
+    if let Outcome::Disagreement { report } = wasmi::wasmi_execute(&data) {
+        panic!("fuzz_wasmi_execute panic:\n{}", report);
+    }
+}
 // debug target
-pub fn debug_wasmi_validate(data: &[u8]) -> bool {
+pub fn debug_wasmi_validate(data: &[u8]) -> Outcome {
     wasmi::wasmi_validate(&data)
 }
-pub fn debug_wasmi_instantiate(data: &[u8]) -> bool {
+pub fn debug_wasmi_instantiate(data: &[u8]) -> Outcome {
     wasmi::wasmi_instantiate(&data)
 }
+pub fn debug_wasmi_execute(data: &[u8]) -> Outcome {
+    wasmi::wasmi_execute(&data)
+}
 
 mod parity_wasm;
 // fuzzing harnesses
 pub fn fuzz_parity_wasm_deserialize(data: &[u8]) {
     let _ = parity_wasm::parity_wasm_deserialize(&data);
 }
+pub fn fuzz_parity_wasm_roundtrip(data: &[u8]) {
+    if let Outcome::Disagreement { report } = parity_wasm::parity_wasm_roundtrip(&data) {
+        panic!("fuzz_parity_wasm_roundtrip panic: {}", report);
+    }
+}
 // debug target
-pub fn debug_parity_wasm_deserialize(data: &[u8]) -> bool {
+pub fn debug_parity_wasm_deserialize(data: &[u8]) -> Outcome {
     parity_wasm::parity_wasm_deserialize(&data)
 }
+pub fn debug_parity_wasm_roundtrip(data: &[u8]) -> Outcome {
+    parity_wasm::parity_wasm_roundtrip(&data)
+}
 
 mod wasmer;
 // fuzzing harnesses
@@ -155,16 +350,16 @@ pub fn fuzz_wasmer_instantiate(data: &[u8]) {
     let _ = wasmer::fuzz_wasmer_instantiate(&data);
 }
 // debug target
-pub fn debug_wasmer_validate(data: &[u8]) -> bool {
+pub fn debug_wasmer_validate(data: &[u8]) -> Outcome {
     wasmer::fuzz_wasmer_validate(&data)
 }
-pub fn debug_wasmer_compile_clif(data: &[u8]) -> bool {
+pub fn debug_wasmer_compile_clif(data: &[u8]) -> Outcome {
     wasmer::fuzz_wasmer_compile_clif(&data)
 }
-pub fn debug_wasmer_compile_singlepass(data: &[u8]) -> bool {
+pub fn debug_wasmer_compile_singlepass(data: &[u8]) -> Outcome {
     wasmer::fuzz_wasmer_compile_singlepass(&data)
 }
-pub fn debug_wasmer_instantiate(data: &[u8]) -> bool {
+pub fn debug_wasmer_instantiate(data: &[u8]) -> Outcome {
     wasmer::fuzz_wasmer_instantiate(&data)
 }
 
@@ -196,28 +391,28 @@ pub fn _fuzz_wasmtime_instantiate_all_lightbeam(data: &[u8]) {
 }
 */
 // debug target
-pub fn debug_wasmtime_validate(data: &[u8]) -> bool {
+pub fn debug_wasmtime_validate(data: &[u8]) -> Outcome {
     wasmtime::fuzz_wasmtime_validate(&data)
 }
-pub fn debug_wasmtime_validate_all_feat(data: &[u8]) -> bool {
+pub fn debug_wasmtime_validate_all_feat(data: &[u8]) -> Outcome {
     wasmtime::fuzz_wasmtime_validate_all_feat(&data)
 }
-pub fn debug_wasmtime_compile(data: &[u8]) -> bool {
+pub fn debug_wasmtime_compile(data: &[u8]) -> Outcome {
     wasmtime::fuzz_wasmtime_compile(&data)
 }
-pub fn debug_wasmtime_compile_all_cranelift(data: &[u8]) -> bool {
+pub fn debug_wasmtime_compile_all_cranelift(data: &[u8]) -> Outcome {
     wasmtime::fuzz_wasmtime_compile_all_cranelift(&data)
 }
 /* DEACTIVATED FOR NOW
-pub fn _debug_wasmtime_compile_all_lightbeam(data: &[u8]) -> bool {
+pub fn _debug_wasmtime_compile_all_lightbeam(data: &[u8]) -> Outcome {
     wasmtime::fuzz_wasmtime_compile_all_lightbeam(data)
 }
 */
-pub fn debug_wasmtime_instantiate_all_cranelift(data: &[u8]) -> bool {
+pub fn debug_wasmtime_instantiate_all_cranelift(data: &[u8]) -> Outcome {
     wasmtime::fuzz_wasmtime_instantiate_all_cranelift(&data)
 }
 /* DEACTIVATED FOR NOW
-pub fn _debug_wasmtime_instantiate_all_lightbeam(data: &[u8]) -> bool {
+pub fn _debug_wasmtime_instantiate_all_lightbeam(data: &[u8]) -> Outcome {
     wasmtime::fuzz_wasmtime_instantiate_all_lightbeam(data)
 }
 
@@ -227,11 +422,45 @@ pub fn _fuzz_lightbeam_translate(data: &[u8]) {
     let _ = lightbeam::fuzz_lightbeam_translate(data);
 }
 // debug target
-pub fn _debug_lightbeam_translate(data: &[u8]) -> bool {
+pub fn _debug_lightbeam_translate(data: &[u8]) -> Outcome {
     lightbeam::fuzz_lightbeam_translate(data)
 }
 */
 
+mod cranelift;
+// fuzzing harnesses
+pub fn fuzz_cranelift_translate(data: &[u8]) {
+    let _ = cranelift::fuzz_cranelift_translate(&data);
+}
+pub fn fuzz_cranelift_compile_x86_64(data: &[u8]) {
+    if let Outcome::Disagreement { report } = cranelift::fuzz_cranelift_compile_x86_64(&data) {
+        panic!("fuzz_cranelift_compile_x86_64 panic:\n{}", report);
+    }
+}
+pub fn fuzz_cranelift_compile_aarch64(data: &[u8]) {
+    if let Outcome::Disagreement { report } = cranelift::fuzz_cranelift_compile_aarch64(&data) {
+        panic!("fuzz_cranelift_compile_aarch64 panic:\n{}", report);
+    }
+}
+pub fn fuzz_cranelift_compile_s390x(data: &[u8]) {
+    if let Outcome::Disagreement { report } = cranelift::fuzz_cranelift_compile_s390x(&data) {
+        panic!("fuzz_cranelift_compile_s390x panic:\n{}", report);
+    }
+}
+// debug target
+pub fn debug_cranelift_translate(data: &[u8]) -> Outcome {
+    cranelift::fuzz_cranelift_translate(&data)
+}
+pub fn debug_cranelift_compile_x86_64(data: &[u8]) -> Outcome {
+    cranelift::fuzz_cranelift_compile_x86_64(&data)
+}
+pub fn debug_cranelift_compile_aarch64(data: &[u8]) -> Outcome {
+    cranelift::fuzz_cranelift_compile_aarch64(&data)
+}
+pub fn debug_cranelift_compile_s390x(data: &[u8]) -> Outcome {
+    cranelift::fuzz_cranelift_compile_s390x(&data)
+}
+
 mod wasmparser;
 // fuzzing harnesses
 pub fn fuzz_wasmparser_parser(data: &[u8]) {
@@ -244,13 +473,13 @@ pub fn fuzz_wasmparser_validate_all_feat(data: &[u8]) {
     let _ = wasmparser::fuzz_wasmparser_validate_all_feat(&data);
 }
 // debug target
-pub fn debug_wasmparser_parser(data: &[u8]) -> bool {
+pub fn debug_wasmparser_parser(data: &[u8]) -> Outcome {
     wasmparser::fuzz_wasmparser_parser(&data)
 }
-pub fn debug_wasmparser_validate(data: &[u8]) -> bool {
+pub fn debug_wasmparser_validate(data: &[u8]) -> Outcome {
     wasmparser::fuzz_wasmparser_validate(&data)
 }
-pub fn debug_wasmparser_validate_all_feat(data: &[u8]) -> bool {
+pub fn debug_wasmparser_validate_all_feat(data: &[u8]) -> Outcome {
     wasmparser::fuzz_wasmparser_validate_all_feat(&data)
 }
 
@@ -262,13 +491,21 @@ pub fn fuzz_binaryen_ffi(data: &[u8]) {
 pub fn fuzz_binaryen_optimize_ffi(data: &[u8]) {
     let _ = binaryen_ffi::fuzz_binaryen_optimize_ffi(&data);
 }
+pub fn fuzz_binaryen_roundtrip_ffi(data: &[u8]) {
+    if let Outcome::Disagreement { report } = binaryen_ffi::fuzz_binaryen_roundtrip_ffi(&data) {
+        panic!("fuzz_binaryen_roundtrip_ffi panic: {}", report);
+    }
+}
 // debug target
-pub fn debug_binaryen_ffi(data: &[u8]) -> bool {
+pub fn debug_binaryen_ffi(data: &[u8]) -> Outcome {
     binaryen_ffi::fuzz_binaryen_ffi(&data)
 }
-pub fn debug_binaryen_optimize_ffi(data: &[u8]) -> bool {
+pub fn debug_binaryen_optimize_ffi(data: &[u8]) -> Outcome {
     binaryen_ffi::fuzz_binaryen_optimize_ffi(&data)
 }
+pub fn debug_binaryen_roundtrip_ffi(data: &[u8]) -> Outcome {
+    binaryen_ffi::fuzz_binaryen_roundtrip_ffi(&data)
+}
 
 mod wabt_ffi;
 
@@ -281,34 +518,63 @@ pub fn fuzz_wabt_validate_ffi(data: &[u8]) {
 pub fn fuzz_wabt_wat2wasm_ffi(data: &[u8]) {
     let _ = wabt_ffi::fuzz_wabt_wat2wasm_ffi(&data);
 }
+pub fn fuzz_wabt_roundtrip_ffi(data: &[u8]) {
+    if let Outcome::Disagreement { report } = wabt_ffi::fuzz_wabt_roundtrip_ffi(&data) {
+        panic!("fuzz_wabt_roundtrip_ffi panic: {}", report);
+    }
+}
 
 // debug target
-pub fn debug_wabt_wasm2wat_all_feat_ffi(data: &[u8]) -> bool {
+pub fn debug_wabt_wasm2wat_all_feat_ffi(data: &[u8]) -> Outcome {
     wabt_ffi::fuzz_wabt_wasm2wat_all_feat_ffi(&data)
 }
-pub fn debug_wabt_validate_ffi(data: &[u8]) -> bool {
+pub fn debug_wabt_validate_ffi(data: &[u8]) -> Outcome {
     wabt_ffi::fuzz_wabt_validate_ffi(&data)
 }
-pub fn debug_wabt_wat2wasm_ffi(data: &[u8]) -> bool {
+pub fn debug_wabt_wat2wasm_ffi(data: &[u8]) -> Outcome {
     wabt_ffi::fuzz_wabt_wat2wasm_ffi(&data)
 }
+pub fn debug_wabt_roundtrip_ffi(data: &[u8]) -> Outcome {
+    wabt_ffi::fuzz_wabt_roundtrip_ffi(&data)
+}
 
 mod wasm3;
 pub fn fuzz_wasm3_parser_ffi(data: &[u8]) {
     let _ = wasm3::fuzz_wasm3_parser_ffi(&data);
 }
-pub fn debug_wasm3_parser_ffi(data: &[u8]) -> bool {
+pub fn fuzz_wasm3_execute_ffi(data: &[u8]) {
+    let _ = wasm3::fuzz_wasm3_execute_ffi(&data);
+}
+pub fn debug_wasm3_parser_ffi(data: &[u8]) -> Outcome {
     wasm3::fuzz_wasm3_parser_ffi(&data)
 }
+pub fn debug_wasm3_execute_ffi(data: &[u8]) -> Outcome {
+    let (outcome, skipped) = wasm3::wasm3_execute_ffi(&data);
+    if skipped > 0 {
+        println!(
+            "wasm3: {} exported functions skipped (signature not supported)",
+            skipped
+        );
+    }
+    outcome
+}
 
 mod wasmprinter;
 pub fn fuzz_wasmprinter_parser(data: &[u8]) {
     let _ = wasmprinter::fuzz_wasmprinter_parser(&data);
 }
+pub fn fuzz_wasmprinter_wat_roundtrip(data: &[u8]) {
+    if let Outcome::Disagreement { report } = wasmprinter::wasmprinter_wat_roundtrip(&data) {
+        panic!("fuzz_wasmprinter_wat_roundtrip panic: {}", report);
+    }
+}
 
-pub fn debug_wasmprinter_parser(data: &[u8]) -> bool {
+pub fn debug_wasmprinter_parser(data: &[u8]) -> Outcome {
     wasmprinter::fuzz_wasmprinter_parser(&data)
 }
+pub fn debug_wasmprinter_wat_roundtrip(data: &[u8]) -> Outcome {
+    wasmprinter::wasmprinter_wat_roundtrip(&data)
+}
 
 mod wain;
 pub fn fuzz_wain_parser(data: &[u8]) {
@@ -317,19 +583,25 @@ pub fn fuzz_wain_parser(data: &[u8]) {
 pub fn fuzz_wain_validate(data: &[u8]) {
     let _ = wain::fuzz_wain_validate(&data);
 }
+pub fn fuzz_wain_execute(data: &[u8]) {
+    let _ = wain::fuzz_wain_execute(&data);
+}
 
-pub fn debug_wain_parser(data: &[u8]) -> bool {
+pub fn debug_wain_parser(data: &[u8]) -> Outcome {
     wain::fuzz_wain_parser(&data)
 }
-pub fn debug_wain_validate(data: &[u8]) -> bool {
+pub fn debug_wain_validate(data: &[u8]) -> Outcome {
     wain::fuzz_wain_validate(&data)
 }
+pub fn debug_wain_execute(data: &[u8]) -> Outcome {
+    wain::fuzz_wain_execute(&data)
+}
 
 mod wat;
 pub fn fuzz_wat_parser(data: &[u8]) {
     let _ = wat::wat_parser(&data);
 }
-pub fn debug_wat_parser(data: &[u8]) -> bool {
+pub fn debug_wat_parser(data: &[u8]) -> Outcome {
     wat::wat_parser(&data)
 }
 
@@ -337,14 +609,34 @@ mod wast;
 pub fn fuzz_wast_parser(data: &[u8]) {
     let _ = wast::wast_parser(&data);
 }
-pub fn debug_wast_parser(data: &[u8]) -> bool {
+pub fn fuzz_wast_execute(data: &[u8]) {
+    if let Outcome::Disagreement { report } = wast::wast_execute(&data) {
+        panic!("fuzz_wast_execute panic:\n{}", report);
+    }
+}
+pub fn debug_wast_parser(data: &[u8]) -> Outcome {
     wast::wast_parser(&data)
 }
+pub fn debug_wast_execute(data: &[u8]) -> Outcome {
+    wast::wast_execute(&data)
+}
 
 mod fizzy;
 pub fn fuzz_fizzy_validate(data: &[u8]) {
     let _ = fizzy::fizzy_validate(&data);
 }
-pub fn debug_fizzy_validate(data: &[u8]) -> bool {
+pub fn fuzz_fizzy_instantiate(data: &[u8]) {
+    let _ = fizzy::fizzy_instantiate(&data);
+}
+pub fn fuzz_fizzy_execute(data: &[u8]) {
+    let _ = fizzy::fizzy_execute(&data);
+}
+pub fn debug_fizzy_validate(data: &[u8]) -> Outcome {
     fizzy::fizzy_validate(&data)
 }
+pub fn debug_fizzy_instantiate(data: &[u8]) -> Outcome {
+    fizzy::fizzy_instantiate(&data)
+}
+pub fn debug_fizzy_execute(data: &[u8]) -> Outcome {
+    fizzy::fizzy_execute(&data)
+}
@@ -3,12 +3,17 @@ LIGHTBEAM:
 - https://github.com/bytecodealliance/wasmtime/tree/master/crates/lightbeam
 ***********************************************/
 
+use crate::outcome::{Outcome, Stage};
+
 /// Fuzzing `lightbeam::translate` using translate methods.
 ///
 /// NOTE: lightbeam not called the same way here than in wasmtime.
 /// NOTE: I'm not sure this method validate the module first.
-pub fn fuzz_lightbeam_translate(data: &[u8]) -> bool {
+pub fn fuzz_lightbeam_translate(data: &[u8]) -> Outcome {
     use lightbeam::translate;
 
-    translate(&data).is_ok()
+    match translate(&data) {
+        Ok(_) => Outcome::Accepted,
+        Err(e) => Outcome::rejected(Stage::Compile, e),
+    }
 }
@@ -0,0 +1,121 @@
+/***********************************************
+OUTCOME (returned by all harnesses)
+************************************************/
+
+use crate::execution::TrapKind;
+use std::fmt;
+
+/// Step of the processing of a module where it has been rejected.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Stage {
+    /// Decoding of the binary or text format.
+    Parse,
+    Validate,
+    Compile,
+    /// Resolution of the imports.
+    Link,
+    Instantiate,
+    /// Printing into another format (e.g. wasm2wat).
+    Print,
+}
+
+impl fmt::Display for Stage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let stage = match self {
+            Stage::Parse => "parse",
+            Stage::Validate => "validate",
+            Stage::Compile => "compile",
+            Stage::Link => "link",
+            Stage::Instantiate => "instantiate",
+            Stage::Print => "print",
+        };
+        write!(f, "{}", stage)
+    }
+}
+
+/// Result of a harness on one input.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    /// The module went through all the steps of the harness.
+    Accepted,
+    Rejected {
+        stage: Stage,
+        message: String,
+    },
+    /// The execution of the module trapped.
+    Trapped {
+        kind: TrapKind,
+    },
+    /// The module uses something the harness can't handle
+    /// (e.g. imports that can't be stubbed).
+    Unsupported,
+    /// Differential and round-trip harnesses only: the runtimes
+    /// disagree, or the re-encoded module doesn't match the original.
+    Disagreement {
+        report: String,
+    },
+}
+
+impl Outcome {
+    pub fn rejected<M: ToString>(stage: Stage, message: M) -> Outcome {
+        Outcome::Rejected {
+            stage,
+            message: message.to_string(),
+        }
+    }
+
+    pub fn is_accepted(&self) -> bool {
+        *self == Outcome::Accepted
+    }
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Outcome::Accepted => write!(f, "accepted"),
+            Outcome::Rejected { stage, message } => {
+                write!(f, "rejected at {}: {}", stage, message.trim_end())
+            }
+            Outcome::Trapped { kind } => write!(f, "trapped: {:?}", kind),
+            Outcome::Unsupported => write!(f, "unsupported"),
+            Outcome::Disagreement { report } => write!(f, "disagreement\n{}", report.trim_end()),
+        }
+    }
+}
+
+/// Compare the outcomes of different implementations on the same input.
+///
+/// Implementations agree when they all accept the module or all
+/// refuse it, whatever the reason. `Unsupported` outcomes and traps that
+/// are not comparable across runtimes are ignored.
+/// Return the common outcome of the first implementation, or a
+/// `Disagreement` listing all the outcomes.
+pub fn diff_outcomes(outcomes: &[(&str, Outcome)]) -> Outcome {
+    use std::fmt::Write;
+
+    // Traps specific to a runtime (e.g. out of fuel) are inconclusive
+    let compared: Vec<&(&str, Outcome)> = outcomes
+        .iter()
+        .filter(|(_, o)| match o {
+            Outcome::Unsupported => false,
+            Outcome::Trapped { kind } => kind.is_comparable(),
+            _ => true,
+        })
+        .collect();
+    let first = match compared.first() {
+        Some((_, o)) => o,
+        None => return Outcome::Unsupported,
+    };
+    if compared
+        .iter()
+        .all(|(_, o)| o.is_accepted() == first.is_accepted())
+    {
+        return first.clone();
+    }
+
+    let mut report = String::new();
+    for (name, outcome) in outcomes {
+        let _ = writeln!(report, "  {}: {}", name, outcome);
+    }
+    Outcome::Disagreement { report }
+}
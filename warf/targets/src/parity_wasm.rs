@@ -3,13 +3,86 @@ parity-wasm:
 - https://github.com/paritytech/parity-wasm
 ************************************************/
 
+use crate::outcome::{Outcome, Stage};
+
 /// Fuzzing `parity_wasm::deserialize_buffer`.
 ///
-/// TODO: Will be more useful with calls to `parity_wasm::elements::Module` accessors.
+/// NOTE: accessors and the encoder are fuzzed by `parity_wasm_roundtrip`.
 /// NOTE: wasmi already use this function in `wasmi::Module::from_buffer`.
-pub fn parity_wasm_deserialize(data: &[u8]) -> bool {
+pub fn parity_wasm_deserialize(data: &[u8]) -> Outcome {
     use parity_wasm::{deserialize_buffer, elements};
 
     let module: std::result::Result<elements::Module, _> = deserialize_buffer(&data);
-    module.is_ok()
+    match module {
+        Ok(_) => Outcome::Accepted,
+        Err(e) => Outcome::rejected(Stage::Parse, e),
+    }
+}
+
+/// Call the accessors of all the sections of `module`.
+fn walk_sections(module: &parity_wasm::elements::Module) -> usize {
+    use parity_wasm::elements::ImportCountType;
+
+    let mut count = module.sections().len();
+    count += module.type_section().map_or(0, |s| s.types().len());
+    count += module.import_section().map_or(0, |s| s.entries().len());
+    count += module.function_section().map_or(0, |s| s.entries().len());
+    count += module.table_section().map_or(0, |s| s.entries().len());
+    count += module.memory_section().map_or(0, |s| s.entries().len());
+    count += module.global_section().map_or(0, |s| s.entries().len());
+    count += module.export_section().map_or(0, |s| s.entries().len());
+    count += module.start_section().map_or(0, |index| index as usize);
+    count += module.elements_section().map_or(0, |s| s.entries().len());
+    count += module.data_section().map_or(0, |s| s.entries().len());
+    count += module.code_section().map_or(0, |s| s.bodies().len());
+    count += module.names_section().map_or(0, |_| 1);
+    count += module.custom_sections().count();
+    count += module.import_count(ImportCountType::Function);
+    count += module.import_count(ImportCountType::Global);
+    count += module.import_count(ImportCountType::Table);
+    count += module.import_count(ImportCountType::Memory);
+    count += module.functions_space();
+    count += module.globals_space();
+    count += module.table_space();
+    count += module.memory_space();
+    count
+}
+
+/// Deserialize the module then parse its name and relocation sections.
+///
+/// NOTE: invalid name or relocation sections are kept as custom sections.
+fn deserialize(data: &[u8]) -> Result<parity_wasm::elements::Module, parity_wasm::elements::Error> {
+    let module: parity_wasm::elements::Module = parity_wasm::deserialize_buffer(&data)?;
+    let module = module.parse_names().unwrap_or_else(|(_, module)| module);
+    let module = module.parse_reloc().unwrap_or_else(|(_, module)| module);
+    Ok(module)
+}
+
+/// Fuzzing parity-wasm encoder: deserialize the module, call all the
+/// section accessors, serialize it then deserialize it again.
+/// Both deserialized modules must be equal.
+pub fn parity_wasm_roundtrip(data: &[u8]) -> Outcome {
+    let module = match deserialize(&data) {
+        Ok(module) => module,
+        Err(e) => return Outcome::rejected(Stage::Parse, e),
+    };
+    let _ = walk_sections(&module);
+
+    let bytes = match parity_wasm::serialize(module.clone()) {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            return Outcome::Disagreement {
+                report: format!("parity-wasm failed to serialize the module: {}", e),
+            }
+        }
+    };
+    match deserialize(&bytes) {
+        Ok(reparsed) if reparsed == module => Outcome::Accepted,
+        Ok(_) => Outcome::Disagreement {
+            report: "serialized module deserializes differently".to_string(),
+        },
+        Err(e) => Outcome::Disagreement {
+            report: format!("parity-wasm rejected the serialized module: {}", e),
+        },
+    }
 }
@@ -0,0 +1,28 @@
+/***********************************************
+wasm-smith (structure-aware generation)
+- https://github.com/bytecodealliance/wasm-tools/tree/main/crates/wasm-smith
+************************************************/
+
+use crate::outcome::Outcome;
+
+/// Generate an always-valid module from the fuzz input.
+///
+/// NOTE: the fuzz input is consumed by `arbitrary::Unstructured` and is not
+/// a wasm module itself, existing wasm corpora only serve as random seeds.
+pub fn smith_module(data: &[u8]) -> Option<Vec<u8>> {
+    use arbitrary::{Arbitrary, Unstructured};
+    use wasm_smith::Module;
+
+    let mut u = Unstructured::new(&data);
+    Module::arbitrary(&mut u)
+        .ok()
+        .map(|module| module.to_bytes())
+}
+
+/// Run `harness` on the module generated from the fuzz input.
+pub fn with_smith(data: &[u8], harness: fn(&[u8]) -> Outcome) -> Outcome {
+    match smith_module(&data) {
+        Some(module) => harness(&module),
+        None => Outcome::Unsupported,
+    }
+}
@@ -3,37 +3,125 @@ WABT (Rust binding using FFI)
 - https://github.com/pepyakin/wabt-rs
 ************************************************/
 
+use crate::features::Features;
+use crate::outcome::{Outcome, Stage};
+
 /// Fuzzing `wabt::wasm2wat_with_features` with all features enabled.
 ///
 /// NOTE: We are fuzzing binaryen over FFI.
 /// TODO: Verify if this implementation validate module first.
 /// TODO: Verify that binary contains coverage for C++ code of wabt.
-pub fn fuzz_wabt_wasm2wat_all_feat_ffi(data: &[u8]) -> bool {
+pub fn fuzz_wabt_wasm2wat_all_feat_ffi(data: &[u8]) -> Outcome {
     use wabt::{wasm2wat_with_features, Features};
 
     let mut features = Features::new();
     features.enable_all();
-    wasm2wat_with_features(&data, features).is_ok()
+    match wasm2wat_with_features(&data, features) {
+        Ok(_) => Outcome::Accepted,
+        Err(e) => Outcome::rejected(Stage::Print, format!("{:?}", e)),
+    }
 }
 
 /// Fuzzing `wabt::Module::{read_binary, validate}` with default features.
-pub fn fuzz_wabt_validate_ffi(data: &[u8]) -> bool {
+pub fn fuzz_wabt_validate_ffi(data: &[u8]) -> Outcome {
     use wabt::{Module, ReadBinaryOptions};
 
     // Default wasm features sets by `Module::read_binary`.
-    match Module::read_binary(&data, &ReadBinaryOptions::default()) {
-        Ok(module) => module.validate().is_ok(),
-        _ => false,
+    let module = match Module::read_binary(&data, &ReadBinaryOptions::default()) {
+        Ok(module) => module,
+        Err(e) => return Outcome::rejected(Stage::Parse, format!("{:?}", e)),
+    };
+    match module.validate() {
+        Ok(_) => Outcome::Accepted,
+        Err(e) => Outcome::rejected(Stage::Validate, format!("{:?}", e)),
+    }
+}
+
+/// Fuzzing wabt validation with the proposals of `features`.
+pub fn fuzz_wabt_validate_with_features_ffi(data: &[u8], features: Features) -> Outcome {
+    use wabt::{Module, ReadBinaryOptions};
+
+    let mut wabt_features = wabt::Features::new();
+    wabt_features.enable_sign_extension();
+    wabt_features.enable_sat_float_to_int();
+    wabt_features.set_simd_enabled(features.simd);
+    wabt_features.set_threads_enabled(features.threads);
+    wabt_features.set_reference_types_enabled(features.reference_types);
+    wabt_features.set_bulk_memory_enabled(features.bulk_memory);
+    wabt_features.set_multi_value_enabled(features.multi_value);
+
+    let options = ReadBinaryOptions {
+        features: wabt_features,
+        ..ReadBinaryOptions::default()
+    };
+    let module = match Module::read_binary(&data, &options) {
+        Ok(module) => module,
+        Err(e) => return Outcome::rejected(Stage::Parse, format!("{:?}", e)),
+    };
+    match module.validate() {
+        Ok(_) => Outcome::Accepted,
+        Err(e) => Outcome::rejected(Stage::Validate, format!("{:?}", e)),
     }
 }
 
-pub fn fuzz_wabt_wat2wasm_ffi(data: &[u8]) -> bool {
+pub fn fuzz_wabt_wat2wasm_ffi(data: &[u8]) -> Outcome {
     use wabt::{wat2wasm_with_features, Features};
 
     let mut features = Features::new();
     features.enable_all();
 
-    wat2wasm_with_features(&data, features).is_ok()
+    match wat2wasm_with_features(&data, features) {
+        Ok(_) => Outcome::Accepted,
+        Err(e) => Outcome::rejected(Stage::Parse, format!("{:?}", e)),
+    }
+}
+
+/// Fuzzing wabt printer and parser: convert the module to text, back to
+/// binary, and check that the result is accepted.
+///
+/// The binary produced by the round-trip is then canonical (e.g. no more
+/// name section), so a second round-trip must give the same binary.
+pub fn fuzz_wabt_roundtrip_ffi(data: &[u8]) -> Outcome {
+    use wabt::{
+        wasm2wat_with_features, wat2wasm_with_features, Features, Module, ReadBinaryOptions,
+    };
+
+    let mut features = Features::new();
+    features.enable_all();
+
+    // Only valid modules are expected to round-trip
+    let options = ReadBinaryOptions {
+        features: features.clone(),
+        ..ReadBinaryOptions::default()
+    };
+    let module = match Module::read_binary(&data, &options) {
+        Ok(module) => module,
+        Err(e) => return Outcome::rejected(Stage::Parse, format!("{:?}", e)),
+    };
+    if let Err(e) = module.validate() {
+        return Outcome::rejected(Stage::Validate, format!("{:?}", e));
+    }
+
+    let roundtrip = |wasm: &[u8]| -> Result<Vec<u8>, String> {
+        let wat = wasm2wat_with_features(wasm, features.clone())
+            .map_err(|e| format!("wasm2wat failed: {:?}", e))?;
+        wat2wasm_with_features(wat, features.clone())
+            .map_err(|e| format!("wat2wasm failed: {:?}", e))
+    };
+    let first = match roundtrip(&data) {
+        Ok(wasm) => wasm,
+        Err(report) => return Outcome::Disagreement { report },
+    };
+    let second = match roundtrip(&first) {
+        Ok(wasm) => wasm,
+        Err(report) => return Outcome::Disagreement { report },
+    };
+    if first != second {
+        return Outcome::Disagreement {
+            report: "canonical module changed after a second round-trip".to_string(),
+        };
+    }
+    Outcome::Accepted
 }
 
 // TODO(RM4) - Module::parse_wat
@@ -3,22 +3,79 @@ wain
 - https://github.com/rhysd/wain
 ************************************************/
 
+use crate::outcome::{Outcome, Stage};
 use wain_syntax_binary::parse;
 use wain_validate::validate;
 
-pub fn fuzz_wain_parser(data: &[u8]) -> bool {
+pub fn fuzz_wain_parser(data: &[u8]) -> Outcome {
     // Parse binary into syntax tree
     match parse(&data) {
-        Ok(_) => true,
-        Err(_) => false,
+        Ok(_) => Outcome::Accepted,
+        Err(e) => Outcome::rejected(Stage::Parse, e),
     }
 }
 
-pub fn fuzz_wain_validate(data: &[u8]) -> bool {
+pub fn fuzz_wain_validate(data: &[u8]) -> Outcome {
     // Parse binary into syntax tree
-    match parse(&data) {
-        // Validate module
-        Ok(tree) => validate(&tree).is_ok(),
-        Err(_) => false,
+    let tree = match parse(&data) {
+        Ok(tree) => tree,
+        Err(e) => return Outcome::rejected(Stage::Parse, e),
+    };
+    // Validate module
+    match validate(&tree) {
+        Ok(_) => Outcome::Accepted,
+        Err(e) => Outcome::rejected(Stage::Validate, e),
+    }
+}
+
+/// Fuzzing wain interpreter: run the start function then call all
+/// exported functions with arguments built from the fuzz input.
+///
+/// NOTE: only the imports of `DefaultImporter` (`env.putchar` and
+/// `env.getchar`) are available, with empty stdin and stdout.
+/// NOTE: wain has no fuel, infinite loops end with a fuzzer timeout.
+pub fn fuzz_wain_execute(data: &[u8]) -> Outcome {
+    use crate::execution::{exported_signatures, split_input, ArgsReader, Value};
+    use std::io;
+    use wain_exec::{DefaultImporter, Runtime};
+
+    let (data, args) = split_input(data);
+
+    // Parse binary into syntax tree
+    let tree = match parse(&data) {
+        Ok(tree) => tree,
+        Err(e) => return Outcome::rejected(Stage::Parse, e),
+    };
+    // Validate module, wain interpreter expects a valid module
+    if let Err(e) = validate(&tree) {
+        return Outcome::rejected(Stage::Validate, e);
+    }
+    let importer = DefaultImporter::with_stdio(io::empty(), io::sink());
+    let mut runtime = match Runtime::instantiate(&tree.module, importer) {
+        Ok(runtime) => runtime,
+        Err(e) => return Outcome::rejected(Stage::Instantiate, e),
+    };
+    // Run the start function
+    if let Err(e) = runtime.start() {
+        return Outcome::Trapped {
+            kind: crate::execution::TrapKind::from_message(&e.to_string()),
+        };
+    }
+
+    let mut reader = ArgsReader::new(args);
+    for (name, signature) in exported_signatures(&data) {
+        let args: Vec<wain_exec::Value> = reader
+            .args(&signature.params)
+            .into_iter()
+            .map(|value| match value {
+                Value::I32(v) => wain_exec::Value::I32(v),
+                Value::I64(v) => wain_exec::Value::I64(v),
+                Value::F32(bits) => wain_exec::Value::F32(f32::from_bits(bits)),
+                Value::F64(bits) => wain_exec::Value::F64(f64::from_bits(bits)),
+            })
+            .collect();
+        // Traps are expected, only crashes are interesting
+        let _ = runtime.invoke(&name, &args);
     }
+    Outcome::Accepted
 }
@@ -3,13 +3,253 @@ wasm3 (Rust binding using FFI)
 - https://github.com/Veykril/wasm3-rs
 ************************************************/
 
+use crate::outcome::{Outcome, Stage};
 use wasm3::Environment;
 use wasm3::Module;
 
-pub fn fuzz_wasm3_parser_ffi(data: &[u8]) -> bool {
-    let env = Environment::new().expect("Unable to create environment");
-    let _rt = env
-        .create_runtime(1024 * 60)
-        .expect("Unable to create runtime");
-    Module::parse(&env, &data).is_ok()
+pub fn fuzz_wasm3_parser_ffi(data: &[u8]) -> Outcome {
+    let env = match Environment::new() {
+        Ok(env) => env,
+        Err(_) => return Outcome::Unsupported,
+    };
+    let _rt = match env.create_runtime(1024 * 60) {
+        Ok(rt) => rt,
+        Err(_) => return Outcome::Unsupported,
+    };
+    match Module::parse(&env, &data) {
+        Ok(_) => Outcome::Accepted,
+        Err(e) => Outcome::rejected(Stage::Parse, e),
+    }
+}
+
+const MIN_STACK_SIZE: u32 = 1024;
+const MAX_STACK_SIZE: u32 = 1024 * 1024;
+
+/// Runtime stack size derived from the last two bytes of the input.
+///
+/// NOTE: the first bytes of a module are always the same (magic and version).
+fn stack_size(data: &[u8]) -> u32 {
+    let seed = data
+        .iter()
+        .rev()
+        .take(2)
+        .fold(0u32, |acc, byte| (acc << 8) | u32::from(*byte));
+    MIN_STACK_SIZE + seed * 16 % (MAX_STACK_SIZE - MIN_STACK_SIZE)
+}
+
+/// Whether the module imports anything from a WASI module (`wasi_*`).
+fn imports_wasi(data: &[u8]) -> bool {
+    use wasmparser::{Parser, Payload};
+
+    Parser::new(0)
+        .parse_all(&data)
+        .any(|payload| match payload {
+            Ok(Payload::ImportSection(reader)) => reader.into_iter().any(|import| {
+                import
+                    .map(|i| i.module.starts_with("wasi_"))
+                    .unwrap_or(false)
+            }),
+            _ => false,
+        })
+}
+
+/// Fuzzing wasm3 compilation and interpreter: load the module into a
+/// runtime then call all exported functions.
+///
+/// NOTE: wasm3 compiles functions lazily, on their first call.
+/// NOTE: imports are never linked, calling one traps. Modules importing
+/// WASI are `Unsupported`, host WASI must not be reachable from fuzz inputs.
+/// NOTE: the typed API of wasm3-rs needs the signature at compile time,
+/// functions with more than three parameters or more than one result
+/// are skipped, see `wasm3_execute_ffi`.
+pub fn fuzz_wasm3_execute_ffi(data: &[u8]) -> Outcome {
+    wasm3_execute_ffi(&data).0
+}
+
+/// Same as `fuzz_wasm3_execute_ffi`, also returning the number of exported
+/// functions not called because of their signature.
+pub fn wasm3_execute_ffi(data: &[u8]) -> (Outcome, usize) {
+    use crate::execution::{exported_signatures, split_input, ArgsReader};
+
+    let (data, args) = split_input(data);
+    if imports_wasi(&data) {
+        return (Outcome::Unsupported, 0);
+    }
+
+    let env = match Environment::new() {
+        Ok(env) => env,
+        Err(_) => return (Outcome::Unsupported, 0),
+    };
+    let rt = match env.create_runtime(stack_size(&data)) {
+        Ok(rt) => rt,
+        Err(_) => return (Outcome::Unsupported, 0),
+    };
+    let module = match Module::parse(&env, &data) {
+        Ok(module) => module,
+        Err(e) => return (Outcome::rejected(Stage::Parse, e), 0),
+    };
+    let module = match rt.load_module(module) {
+        Ok(module) => module,
+        Err(e) => return (Outcome::rejected(Stage::Instantiate, e), 0),
+    };
+
+    let mut reader = ArgsReader::new(args);
+    let mut skipped = 0;
+    for (name, signature) in exported_signatures(&data) {
+        // Keep the arguments of the next functions in sync with the
+        // other execution harnesses, even if this one is skipped
+        let args = reader.args(&signature.params);
+        if call(&module, &name, &signature, &args).is_none() {
+            skipped += 1;
+        }
+    }
+    (Outcome::Accepted, skipped)
+}
+
+/// Bind `$arg` to the native value of `$value` then evaluate `$call`.
+macro_rules! with_arg {
+    ($value:expr, $arg:ident => $call:expr) => {
+        match $value {
+            Value::I32($arg) => $call,
+            Value::I64($arg) => $call,
+            Value::F32(bits) => {
+                let $arg = f32::from_bits(bits);
+                $call
+            }
+            Value::F64(bits) => {
+                let $arg = f64::from_bits(bits);
+                $call
+            }
+        }
+    };
+}
+
+/// Call the exported function `name` with `args`, `None` if its signature
+/// can't be expressed with the typed API.
+///
+/// NOTE: traps are expected, only crashes are interesting.
+fn call(
+    module: &Module,
+    name: &str,
+    signature: &crate::execution::Signature,
+    args: &[crate::execution::Value],
+) -> Option<Result<(), wasm3::error::Error>> {
+    use crate::execution::Value;
+
+    let result = match signature.results.as_slice() {
+        [] => None,
+        [result] => Some(*result),
+        _ => return None,
+    };
+    let called = match *args {
+        [] => call0(module, name, result),
+        [a] => with_arg!(a, a => call1(module, name, a, result)),
+        [a, b] => with_arg!(a, a => with_arg!(b, b => call2(module, name, (a, b), result))),
+        [a, b, c] => with_arg!(a, a => with_arg!(b, b => with_arg!(c, c => {
+            call3(module, name, (a, b, c), result)
+        }))),
+        _ => return None,
+    };
+    Some(called)
+}
+
+/// Call a function without parameter.
+fn call0(
+    module: &Module,
+    name: &str,
+    result: Option<crate::execution::ValType>,
+) -> Result<(), wasm3::error::Error> {
+    use crate::execution::ValType;
+
+    match result {
+        None => module.find_function::<(), ()>(name)?.call().map(drop),
+        Some(ValType::I32) => module.find_function::<(), i32>(name)?.call().map(drop),
+        Some(ValType::I64) => module.find_function::<(), i64>(name)?.call().map(drop),
+        Some(ValType::F32) => module.find_function::<(), f32>(name)?.call().map(drop),
+        Some(ValType::F64) => module.find_function::<(), f64>(name)?.call().map(drop),
+    }
+}
+
+/// Call a function with one parameter.
+fn call1<A: wasm3::WasmArg>(
+    module: &Module,
+    name: &str,
+    arg: A,
+    result: Option<crate::execution::ValType>,
+) -> Result<(), wasm3::error::Error> {
+    use crate::execution::ValType;
+
+    match result {
+        None => module.find_function::<A, ()>(name)?.call(arg).map(drop),
+        Some(ValType::I32) => module.find_function::<A, i32>(name)?.call(arg).map(drop),
+        Some(ValType::I64) => module.find_function::<A, i64>(name)?.call(arg).map(drop),
+        Some(ValType::F32) => module.find_function::<A, f32>(name)?.call(arg).map(drop),
+        Some(ValType::F64) => module.find_function::<A, f64>(name)?.call(arg).map(drop),
+    }
+}
+
+/// Call a function with two parameters.
+fn call2<A: wasm3::WasmArg, B: wasm3::WasmArg>(
+    module: &Module,
+    name: &str,
+    (a, b): (A, B),
+    result: Option<crate::execution::ValType>,
+) -> Result<(), wasm3::error::Error> {
+    use crate::execution::ValType;
+
+    match result {
+        None => module
+            .find_function::<(A, B), ()>(name)?
+            .call(a, b)
+            .map(drop),
+        Some(ValType::I32) => module
+            .find_function::<(A, B), i32>(name)?
+            .call(a, b)
+            .map(drop),
+        Some(ValType::I64) => module
+            .find_function::<(A, B), i64>(name)?
+            .call(a, b)
+            .map(drop),
+        Some(ValType::F32) => module
+            .find_function::<(A, B), f32>(name)?
+            .call(a, b)
+            .map(drop),
+        Some(ValType::F64) => module
+            .find_function::<(A, B), f64>(name)?
+            .call(a, b)
+            .map(drop),
+    }
+}
+
+/// Call a function with three parameters.
+fn call3<A: wasm3::WasmArg, B: wasm3::WasmArg, C: wasm3::WasmArg>(
+    module: &Module,
+    name: &str,
+    (a, b, c): (A, B, C),
+    result: Option<crate::execution::ValType>,
+) -> Result<(), wasm3::error::Error> {
+    use crate::execution::ValType;
+
+    match result {
+        None => module
+            .find_function::<(A, B, C), ()>(name)?
+            .call(a, b, c)
+            .map(drop),
+        Some(ValType::I32) => module
+            .find_function::<(A, B, C), i32>(name)?
+            .call(a, b, c)
+            .map(drop),
+        Some(ValType::I64) => module
+            .find_function::<(A, B, C), i64>(name)?
+            .call(a, b, c)
+            .map(drop),
+        Some(ValType::F32) => module
+            .find_function::<(A, B, C), f32>(name)?
+            .call(a, b, c)
+            .map(drop),
+        Some(ValType::F64) => module
+            .find_function::<(A, B, C), f64>(name)?
+            .call(a, b, c)
+            .map(drop),
+    }
 }
@@ -3,36 +3,229 @@ WASMER:
 - https://github.com/wasmerio/wasmer
 ************************************************/
 
+use crate::execution::Execution;
+use crate::features::Features;
+use crate::outcome::{Outcome, Stage};
+
 /// Fuzzing `wasmer::validate`
-pub fn fuzz_wasmer_validate(data: &[u8]) -> bool {
-    extern crate wasmer_runtime;
-    wasmer_runtime::validate(&data)
+///
+/// NOTE: `wasmer_runtime::validate` is the same function without the error.
+pub fn fuzz_wasmer_validate(data: &[u8]) -> Outcome {
+    use wasmer_runtime_core::validate_and_report_errors;
+
+    match validate_and_report_errors(&data) {
+        Ok(_) => Outcome::Accepted,
+        Err(e) => Outcome::rejected(Stage::Validate, e),
+    }
+}
+
+/// Fuzzing `wasmer::validate` with the proposals of `features`.
+///
+/// NOTE: wasmer only allows to enable SIMD and threads.
+pub fn fuzz_wasmer_validate_with_features(data: &[u8], features: Features) -> Outcome {
+    use wasmer_runtime_core::backend::Features as WasmerFeatures;
+    use wasmer_runtime_core::validate_and_report_errors_with_features;
+
+    if features.reference_types || features.bulk_memory || features.multi_value {
+        return Outcome::Unsupported;
+    }
+    let features = WasmerFeatures {
+        simd: features.simd,
+        threads: features.threads,
+    };
+    match validate_and_report_errors_with_features(&data, features) {
+        Ok(_) => Outcome::Accepted,
+        Err(e) => Outcome::rejected(Stage::Validate, e),
+    }
 }
 
 /// Fuzzing wasmer::compile with Cranelift compiler backend
-pub fn fuzz_wasmer_compile_clif(data: &[u8]) -> bool {
+pub fn fuzz_wasmer_compile_clif(data: &[u8]) -> Outcome {
     use wasmer_runtime::compile;
-    compile(&data).is_ok()
+    match compile(&data) {
+        Ok(_) => Outcome::Accepted,
+        Err(e) => Outcome::rejected(Stage::Compile, e),
+    }
 }
 
 /// Fuzzing `wasmer::compile` with `SinglePass` compiler backend
-pub fn fuzz_wasmer_compile_singlepass(data: &[u8]) -> bool {
+pub fn fuzz_wasmer_compile_singlepass(data: &[u8]) -> Outcome {
     use wasmer_runtime::compile_with;
     use wasmer_singlepass_backend::SinglePassCompiler;
-    compile_with(&data, &SinglePassCompiler::new()).is_ok()
+    match compile_with(&data, &SinglePassCompiler::new()) {
+        Ok(_) => Outcome::Accepted,
+        Err(e) => Outcome::rejected(Stage::Compile, e),
+    }
+}
+
+/// Fuzzing `wasmer::Module::instantiate` with dummy imports
+pub fn fuzz_wasmer_instantiate(data: &[u8]) -> Outcome {
+    use crate::dummy::wasmer::dummy_imports;
+    use wasmer_runtime::compile;
+
+    let module = match compile(&data) {
+        Ok(module) => module,
+        Err(e) => return Outcome::rejected(Stage::Compile, e),
+    };
+    // Create a stub for every import of the module
+    let import_object = match dummy_imports(&module) {
+        Some(import_object) => import_object,
+        None => return Outcome::Unsupported,
+    };
+    match module.instantiate(&import_object) {
+        Ok(_) => Outcome::Accepted,
+        Err(e) => instantiation_failure(e),
+    }
+}
+
+/// Outcome of a failed `wasmer::Module::instantiate`, a runtime error
+/// coming from the start function.
+fn instantiation_failure(error: wasmer_runtime::error::Error) -> Outcome {
+    use wasmer_runtime::error::Error;
+
+    match error {
+        Error::RuntimeError(e) => Outcome::Trapped {
+            kind: to_trap_kind(&e),
+        },
+        Error::LinkError(_) => Outcome::rejected(Stage::Link, error),
+        _ => Outcome::rejected(Stage::Instantiate, error),
+    }
+}
+
+/// Convert a `wasmer` runtime error into a runtime independent trap.
+fn to_trap_kind(error: &wasmer_runtime::error::RuntimeError) -> crate::execution::TrapKind {
+    use crate::execution::TrapKind;
+    use wasmer_runtime::error::RuntimeError;
+
+    match error {
+        RuntimeError::Metering(_) => TrapKind::OutOfFuel,
+        _ => TrapKind::from_message(&error.to_string()),
+    }
+}
+
+/// Return a `SinglePass` compiler metering the code with `limit` points.
+///
+/// NOTE: the metering middleware is not available with `Cranelift`.
+fn get_metered_compiler(limit: u64) -> impl wasmer_runtime_core::backend::Compiler {
+    use wasmer_middleware_common::metering::Metering;
+    use wasmer_runtime_core::codegen::{MiddlewareChain, StreamingCompiler};
+    use wasmer_singlepass_backend::ModuleCodeGenerator as SinglePassMCG;
+
+    let compiler: StreamingCompiler<SinglePassMCG, _, _, _, _> =
+        StreamingCompiler::new(move || {
+            let mut chain = MiddlewareChain::new();
+            chain.push(Metering::new(limit));
+            chain
+        });
+    compiler
+}
+
+/// Instantiate the module with `SinglePass` backend and dummy imports,
+/// run the start function and call all exported functions.
+///
+/// NOTE: the execution is bounded by `fuel_limit`.
+pub fn wasmer_execute_exports(data: &[u8]) -> Execution {
+    use crate::dummy::wasmer::dummy_imports;
+    use crate::execution::{
+        exported_functions, fuel_limit, split_input, ArgsReader, Call, TrapKind,
+    };
+    use wasmer_runtime::compile_with;
+    use wasmer_runtime::error::CallError;
+
+    let (data, args) = split_input(data);
+
+    let module = match compile_with(&data, &get_metered_compiler(fuel_limit())) {
+        Ok(module) => module,
+        Err(e) => return Execution::Rejected(Outcome::rejected(Stage::Compile, e)),
+    };
+    let import_object = match dummy_imports(&module) {
+        Some(import_object) => import_object,
+        None => return Execution::Rejected(Outcome::Unsupported),
+    };
+    // A trap inside the start function makes the instantiation fail.
+    let instance = match module.instantiate(&import_object) {
+        Ok(instance) => instance,
+        Err(e) => {
+            return match instantiation_failure(e) {
+                Outcome::Trapped { kind } => Execution::StartTrapped(kind),
+                outcome => Execution::Rejected(outcome),
+            }
+        }
+    };
+
+    let mut reader = ArgsReader::new(args);
+    let mut calls = Vec::new();
+    for name in exported_functions(&data) {
+        let func = match instance.dyn_func(&name) {
+            Ok(func) => func,
+            Err(_) => continue,
+        };
+        // Skip functions using types unknown to the other runtimes
+        let params: Option<Vec<_>> = func
+            .signature()
+            .params()
+            .iter()
+            .map(|ty| to_val_type(*ty))
+            .collect();
+        let params = match params {
+            Some(params) => params,
+            None => continue,
+        };
+        let args = reader.args(&params);
+        let values: Vec<_> = args.iter().map(|v| to_value(*v)).collect();
+        let result = match func.call(&values) {
+            Ok(results) => results
+                .into_iter()
+                .map(from_value)
+                .collect::<Option<Vec<_>>>()
+                .ok_or_else(|| TrapKind::Unknown("unsupported result type".to_string())),
+            Err(CallError::Runtime(e)) => Err(to_trap_kind(&e)),
+            Err(e) => Err(TrapKind::Unknown(e.to_string())),
+        };
+        calls.push(Call { name, args, result });
+    }
+    Execution::Executed(calls)
+}
+
+/// Convert a `wasmer` type into a runtime independent type.
+fn to_val_type(ty: wasmer_runtime::types::Type) -> Option<crate::execution::ValType> {
+    use crate::execution::ValType;
+    use wasmer_runtime::types::Type;
+
+    match ty {
+        Type::I32 => Some(ValType::I32),
+        Type::I64 => Some(ValType::I64),
+        Type::F32 => Some(ValType::F32),
+        Type::F64 => Some(ValType::F64),
+        Type::V128 => None,
+    }
+}
+
+/// Convert a runtime independent value into a `wasmer::Value`.
+fn to_value(value: crate::execution::Value) -> wasmer_runtime::Value {
+    use crate::execution::Value;
+    use wasmer_runtime::Value as WasmerValue;
+
+    match value {
+        Value::I32(v) => WasmerValue::I32(v),
+        Value::I64(v) => WasmerValue::I64(v),
+        Value::F32(bits) => WasmerValue::F32(f32::from_bits(bits)),
+        Value::F64(bits) => WasmerValue::F64(f64::from_bits(bits)),
+    }
 }
 
-/// Fuzzing `wasmer::instantiate` with empty import_object
-pub fn fuzz_wasmer_instantiate(data: &[u8]) -> bool {
-    use wasmer_runtime::{imports, instantiate};
-    let import_object = imports! {};
-    // allow_missing_functions should prevent wasmer to reject
-    // modules with imported functions but generate more false positive bugs
-    // import_object.allow_missing_functions = true;
-    instantiate(&data, &import_object).is_ok()
+/// Convert a `wasmer::Value` into a runtime independent value.
+fn from_value(value: wasmer_runtime::Value) -> Option<crate::execution::Value> {
+    use crate::execution::Value;
+    use wasmer_runtime::Value as WasmerValue;
 
-    // TODO(RM3): improve or create new fuzz harness that iterate
-    // over module functions and call them all
+    match value {
+        WasmerValue::I32(v) => Some(Value::I32(v)),
+        WasmerValue::I64(v) => Some(Value::I64(v)),
+        WasmerValue::F32(v) => Some(Value::F32(v.to_bits())),
+        WasmerValue::F64(v) => Some(Value::F64(v.to_bits())),
+        WasmerValue::V128(_) => None,
+    }
 }
 
 /*
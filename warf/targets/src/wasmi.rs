@@ -3,28 +3,313 @@ wasmi:
 - https://github.com/paritytech/wasmi
 ************************************************/
 
+use crate::execution::{Execution, TrapKind};
+use crate::features::{uses_post_mvp_operators, Features};
+use crate::outcome::{Outcome, Stage};
+use crate::wast::Command;
+
 /// Fuzzing `wasmi::validate_module`.
-pub fn wasmi_validate(data: &[u8]) -> bool {
+pub fn wasmi_validate(data: &[u8]) -> Outcome {
     use parity_wasm::{deserialize_buffer, elements};
     use wasmi_validation::{validate_module, PlainValidator};
 
     let module: elements::Module = match deserialize_buffer(&data) {
         Ok(module) => module,
-        _ => return false,
+        Err(e) => return Outcome::rejected(Stage::Parse, e),
     };
-    validate_module::<PlainValidator>(&module).is_ok()
+    match validate_module::<PlainValidator>(&module) {
+        Ok(_) => Outcome::Accepted,
+        Err(e) => Outcome::rejected(Stage::Validate, e),
+    }
+}
+
+/// `wasmi_validate` for the MVP, the only proposal set supported by wasmi.
+pub fn wasmi_validate_with_features(data: &[u8], features: Features) -> Outcome {
+    if !features.is_mvp() || uses_post_mvp_operators(&data) {
+        return Outcome::Unsupported;
+    }
+    wasmi_validate(&data)
+}
+
+/// Fuzzing `wasmi::ModuleInstance` with dummy imports, running the start
+/// function.
+///
+/// NOTE: the start function is bounded by `fuel_limit`, like `wasmi_execute`.
+pub fn wasmi_instantiate(data: &[u8]) -> Outcome {
+    match instantiate_metered(&data) {
+        Ok(_) => Outcome::Accepted,
+        Err(execution) => execution.outcome(),
+    }
+}
+
+/// Fuzzing `wasmi` interpreter: run the start function then call
+/// all exported functions with arguments built from the fuzz input.
+///
+/// NOTE: imported functions are replaced by stubs returning zeros.
+/// NOTE: the execution is bounded by `fuel_limit`. When the metered
+/// module doesn't run out of fuel, the original module is executed too
+/// and a different result is reported as a `Disagreement`.
+pub fn wasmi_execute(data: &[u8]) -> Outcome {
+    use crate::execution::diff_executions;
+
+    let metered = wasmi_execute_exports(&data);
+    if ran_out_of_fuel(&metered) {
+        return metered.outcome();
+    }
+    let outcome = metered.outcome();
+    let original = execute_exports(&data, None);
+    match diff_executions(&[("wasmi (metered)", metered), ("wasmi", original)]) {
+        Some(report) => Outcome::Disagreement { report },
+        None => outcome,
+    }
+}
+
+/// Instantiate the module with dummy imports, run the start function
+/// and call all exported functions.
+pub fn wasmi_execute_exports(data: &[u8]) -> Execution {
+    use crate::execution::fuel_limit;
+
+    execute_exports(data, Some(fuel_limit()))
 }
 
-/// Fuzzing `wasmi::ModuleInstance` with default `ImportsBuilder`.
-pub fn wasmi_instantiate(data: &[u8]) -> bool {
-    use wasmi::{ImportsBuilder, Module, ModuleInstance};
+/// Same as `wasmi_execute_exports`, metering the module with `fuel`
+/// when given.
+fn execute_exports(data: &[u8], fuel: Option<u64>) -> Execution {
+    use crate::execution::{exported_functions, split_input, ArgsReader, Call};
+    use wasmi::ExternVal;
+
+    let (data, args) = split_input(data);
+
+    let (instance, mut externals) = match instantiate(&data, fuel) {
+        Ok(a) => a,
+        Err(execution) => return execution,
+    };
+
+    let mut reader = ArgsReader::new(args);
+    let mut calls = Vec::new();
+    for name in exported_functions(&data) {
+        let func = match instance.export_by_name(&name) {
+            Some(ExternVal::Func(func)) => func,
+            _ => continue,
+        };
+        let params: Vec<_> = func
+            .signature()
+            .params()
+            .iter()
+            .map(|ty| to_val_type(*ty))
+            .collect();
+        let args = reader.args(&params);
+        let result = invoke(&func, &args, &mut externals);
+        calls.push(Call { name, args, result });
+    }
+    Execution::Executed(calls)
+}
 
-    match Module::from_buffer(&data) {
-        Ok(module) => ModuleInstance::new(&module, &ImportsBuilder::default()).is_ok(),
-        _ => false,
+/// Whether the execution has been stopped by the gas counter.
+fn ran_out_of_fuel(execution: &Execution) -> bool {
+    match execution {
+        Execution::StartTrapped(kind) => *kind == TrapKind::OutOfFuel,
+        Execution::Executed(calls) => calls
+            .iter()
+            .any(|call| call.result == Err(TrapKind::OutOfFuel)),
+        Execution::Rejected(_) => false,
     }
+}
 
-    // TODO(RM3): add calls to instance functions like:
-    // - invoke_export: https://github.com/paritytech/wasmi/blob/b67af25899874de7aac187e08e3b2a30d9bbc388/benches/src/lib.rs#L38
-    // - run_start: https://github.com/paritytech/wasmi/blob/899cc32e45483fce12907f807ee9b09d837d2636/examples/interpret.rs#L36
+/// Instantiate the metered module with dummy imports and run the
+/// start function.
+fn instantiate_metered(
+    data: &[u8],
+) -> Result<(wasmi::ModuleRef, crate::dummy::wasmi::DummyExternals), Execution> {
+    use crate::execution::fuel_limit;
+
+    instantiate(data, Some(fuel_limit()))
+}
+
+/// Instantiate the module with dummy imports and run the start function.
+///
+/// wasmi has no fuel of its own: when `fuel` is given, a gas counter is
+/// injected in the module with `pwasm_utils`, calling an imported
+/// function once per basic block.
+///
+/// NOTE: the original module is always validated by wasmi, a module
+/// that `pwasm_utils` fails to meter is `Unsupported`.
+fn instantiate(
+    data: &[u8],
+    fuel: Option<u64>,
+) -> Result<(wasmi::ModuleRef, crate::dummy::wasmi::DummyExternals), Execution> {
+    use crate::dummy::wasmi::{DummyResolver, GAS_MODULE};
+    use parity_wasm::{deserialize_buffer, elements};
+    use pwasm_utils::{inject_gas_counter, rules};
+    use wasmi::{Module, ModuleInstance};
+
+    let original: elements::Module = match deserialize_buffer(&data) {
+        Ok(module) => module,
+        Err(e) => return Err(Execution::Rejected(Outcome::rejected(Stage::Parse, e))),
+    };
+    let module = match Module::from_parity_wasm_module(original.clone()) {
+        Ok(module) => module,
+        Err(e) => return Err(Execution::Rejected(Outcome::rejected(Stage::Validate, e))),
+    };
+    let (module, resolver) = match fuel {
+        None => (module, DummyResolver::default()),
+        Some(fuel) => {
+            // Meter the module, one unit of fuel per instruction
+            let metered = inject_gas_counter(original, &rules::Set::default(), GAS_MODULE)
+                .ok()
+                .and_then(|metered| Module::from_parity_wasm_module(metered).ok());
+            match metered {
+                Some(module) => (module, DummyResolver::with_fuel(fuel)),
+                None => return Err(Execution::Rejected(Outcome::Unsupported)),
+            }
+        }
+    };
+    let instance = match ModuleInstance::new(&module, &resolver) {
+        Ok(instance) => instance,
+        Err(e) => {
+            return Err(Execution::Rejected(Outcome::rejected(
+                Stage::Instantiate,
+                e,
+            )))
+        }
+    };
+    let mut externals = resolver.externals();
+    // A trap inside the start function makes the instantiation fail.
+    match instance.run_start(&mut externals) {
+        Ok(instance) => Ok((instance, externals)),
+        Err(trap) => Err(Execution::StartTrapped(to_trap_kind(trap.kind()))),
+    }
+}
+
+/// Call `func` with `args`.
+fn invoke(
+    func: &wasmi::FuncRef,
+    args: &[crate::execution::Value],
+    externals: &mut crate::dummy::wasmi::DummyExternals,
+) -> crate::execution::CallResult {
+    use wasmi::FuncInstance;
+
+    let runtime_args: Vec<_> = args.iter().map(|v| to_runtime_value(*v)).collect();
+    match FuncInstance::invoke(&func, &runtime_args, externals) {
+        Ok(value) => Ok(value.map(from_runtime_value).into_iter().collect()),
+        Err(wasmi::Error::Trap(trap)) => Err(to_trap_kind(trap.kind())),
+        Err(e) => Err(TrapKind::Unknown(e.to_string())),
+    }
+}
+
+/// Run the commands of a WAST script, returning the result of every
+/// invocation. Modules and invocations that can't be made give `None`.
+///
+/// NOTE: every module is metered like in `wasmi_execute`, with its own fuel.
+pub fn wasmi_run_commands(commands: &[Command]) -> Vec<Option<crate::execution::CallResult>> {
+    use std::collections::HashMap;
+    use wasmi::ExternVal;
+
+    let mut instances = Vec::new();
+    let mut named = HashMap::new();
+    let mut current = None;
+    let mut results = Vec::new();
+    for command in commands {
+        let result = match command {
+            Command::Module { name, wasm } => {
+                current = wasm
+                    .as_ref()
+                    .and_then(|wasm| instantiate_metered(&wasm).ok())
+                    .map(|instance| {
+                        instances.push(instance);
+                        instances.len() - 1
+                    });
+                if let Some(name) = name {
+                    match current {
+                        Some(index) => named.insert(name.clone(), index),
+                        None => named.remove(name),
+                    };
+                }
+                None
+            }
+            Command::Invoke { module, name, args } => {
+                let index = match module {
+                    Some(module) => named.get(module).copied(),
+                    None => current,
+                };
+                index.and_then(|index| {
+                    let (instance, externals) = &mut instances[index];
+                    let func = match instance.export_by_name(&name) {
+                        Some(ExternVal::Func(func)) => func,
+                        _ => return None,
+                    };
+                    // Skip invocations not matching the signature
+                    let params = func.signature().params();
+                    if params.len() != args.len()
+                        || params
+                            .iter()
+                            .zip(args)
+                            .any(|(ty, v)| to_val_type(*ty) != v.ty())
+                    {
+                        return None;
+                    }
+                    Some(invoke(&func, &args, externals))
+                })
+            }
+        };
+        results.push(result);
+    }
+    results
+}
+
+/// Convert a `wasmi::ValueType` into a runtime independent type.
+fn to_val_type(ty: wasmi::ValueType) -> crate::execution::ValType {
+    use crate::execution::ValType;
+    use wasmi::ValueType;
+
+    match ty {
+        ValueType::I32 => ValType::I32,
+        ValueType::I64 => ValType::I64,
+        ValueType::F32 => ValType::F32,
+        ValueType::F64 => ValType::F64,
+    }
+}
+
+/// Convert a runtime independent value into a `wasmi::RuntimeValue`.
+fn to_runtime_value(value: crate::execution::Value) -> wasmi::RuntimeValue {
+    use crate::execution::Value;
+    use wasmi::nan_preserving_float::{F32, F64};
+    use wasmi::RuntimeValue;
+
+    match value {
+        Value::I32(v) => RuntimeValue::I32(v),
+        Value::I64(v) => RuntimeValue::I64(v),
+        Value::F32(bits) => RuntimeValue::F32(F32::from_bits(bits)),
+        Value::F64(bits) => RuntimeValue::F64(F64::from_bits(bits)),
+    }
+}
+
+/// Convert a `wasmi::RuntimeValue` into a runtime independent value.
+fn from_runtime_value(value: wasmi::RuntimeValue) -> crate::execution::Value {
+    use crate::execution::Value;
+    use wasmi::RuntimeValue;
+
+    match value {
+        RuntimeValue::I32(v) => Value::I32(v),
+        RuntimeValue::I64(v) => Value::I64(v),
+        RuntimeValue::F32(v) => Value::F32(v.to_bits()),
+        RuntimeValue::F64(v) => Value::F64(v.to_bits()),
+    }
+}
+
+/// Convert a `wasmi::TrapKind` into a runtime independent trap.
+fn to_trap_kind(kind: &wasmi::TrapKind) -> TrapKind {
+    use crate::dummy::wasmi::OutOfFuel;
+    use wasmi::TrapKind as Kind;
+
+    match kind {
+        Kind::Unreachable => TrapKind::Unreachable,
+        Kind::MemoryAccessOutOfBounds => TrapKind::MemoryOutOfBounds,
+        Kind::TableAccessOutOfBounds | Kind::ElemUninitialized => TrapKind::TableOutOfBounds,
+        Kind::UnexpectedSignature => TrapKind::BadSignature,
+        Kind::DivisionByZero | Kind::InvalidConversionToInt => TrapKind::Arithmetic,
+        Kind::StackOverflow => TrapKind::StackOverflow,
+        Kind::Host(e) if e.downcast_ref::<OutOfFuel>().is_some() => TrapKind::OutOfFuel,
+        Kind::Host(e) => TrapKind::Unknown(e.to_string()),
+    }
 }
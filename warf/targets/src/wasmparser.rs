@@ -3,28 +3,34 @@ WASMPARSER:
 - https://github.com/bytecodealliance/wasmparser
 ************************************************/
 
+use crate::features::Features;
+use crate::outcome::{Outcome, Stage};
+
 /// `Fuzzing wasmparser::Parser` and loop to read all module.
-pub fn fuzz_wasmparser_parser(data: &[u8]) -> bool {
+pub fn fuzz_wasmparser_parser(data: &[u8]) -> Outcome {
     use wasmparser::Parser;
     let res_iter = Parser::new(0).parse_all(&data);
     for res in res_iter {
         match res {
-            Err(_) => return false,
+            Err(e) => return Outcome::rejected(Stage::Parse, e),
             _ => (),
         }
     }
-    true
+    Outcome::Accepted
 }
 
 /// Fuzzing `wasmparser::ValidatingParser` and loop to read all module.
-pub fn fuzz_wasmparser_validate(data: &[u8]) -> bool {
+pub fn fuzz_wasmparser_validate(data: &[u8]) -> Outcome {
     use wasmparser::validate;
 
-    validate(&data).is_ok()
+    match validate(&data) {
+        Ok(_) => Outcome::Accepted,
+        Err(e) => Outcome::rejected(Stage::Validate, e),
+    }
 }
 
 /// Fuzzing `wasmparser::ValidatingParser` with all features enabled and loop to read all module.
-pub fn fuzz_wasmparser_validate_all_feat(data: &[u8]) -> bool {
+pub fn fuzz_wasmparser_validate_all_feat(data: &[u8]) -> Outcome {
     use wasmparser::{Validator, WasmFeatures};
 
     let mut validator = Validator::new();
@@ -42,5 +48,31 @@ pub fn fuzz_wasmparser_validate_all_feat(data: &[u8]) -> bool {
     });
 
     // validate
-    validator.validate_all(&data).is_ok()
+    match validator.validate_all(&data) {
+        Ok(_) => Outcome::Accepted,
+        Err(e) => Outcome::rejected(Stage::Validate, e),
+    }
+}
+
+/// Fuzzing `wasmparser::Validator` with the proposals of `features`.
+pub fn fuzz_wasmparser_validate_with_features(data: &[u8], features: Features) -> Outcome {
+    use wasmparser::{Validator, WasmFeatures};
+
+    let mut validator = Validator::new();
+    validator.wasm_features(WasmFeatures {
+        reference_types: features.reference_types,
+        multi_value: features.multi_value,
+        threads: features.threads,
+        simd: features.simd,
+        module_linking: false,
+        tail_call: false,
+        bulk_memory: features.bulk_memory,
+        deterministic_only: false,
+        multi_memory: false,
+        memory64: false,
+    });
+    match validator.validate_all(&data) {
+        Ok(_) => Outcome::Accepted,
+        Err(e) => Outcome::rejected(Stage::Validate, e),
+    }
 }
@@ -3,6 +3,52 @@ wasmprinter
 - https://github.com/bytecodealliance/wasm-tools/tree/master/crates/wasmprinter
 ************************************************/
 
-pub fn fuzz_wasmprinter_parser(data: &[u8]) -> bool {
-    wasmprinter::print_bytes(&data).is_ok()
+use crate::outcome::{Outcome, Stage};
+
+pub fn fuzz_wasmprinter_parser(data: &[u8]) -> Outcome {
+    match wasmprinter::print_bytes(&data) {
+        Ok(_) => Outcome::Accepted,
+        Err(e) => Outcome::rejected(Stage::Print, e),
+    }
+}
+
+/// Fuzzing consistency of wasmprinter and wat: the text printed by
+/// wasmprinter for a valid module must be accepted by `wat::parse_str`,
+/// and printing then encoding the re-encoded binary must give the same
+/// binary.
+///
+/// NOTE: the re-encoded binary is compared with the second round trip,
+/// not the input, which may use non canonical encodings.
+pub fn wasmprinter_wat_roundtrip(data: &[u8]) -> Outcome {
+    if let Err(e) = wasmparser::validate(&data) {
+        return Outcome::rejected(Stage::Validate, e);
+    }
+    let wasm = match roundtrip(&data) {
+        Ok(wasm) => wasm,
+        Err(outcome) => return outcome,
+    };
+    match roundtrip(&wasm) {
+        Ok(rewasm) if rewasm == wasm => Outcome::Accepted,
+        Ok(_) => Outcome::Disagreement {
+            report: "re-encoded module is not an equivalent binary".to_string(),
+        },
+        Err(Outcome::Rejected { message, .. }) => Outcome::Disagreement {
+            report: format!("wasmprinter rejected the re-encoded module: {}", message),
+        },
+        Err(outcome) => outcome,
+    }
+}
+
+/// Print the module with wasmprinter then encode the text with wat.
+fn roundtrip(data: &[u8]) -> Result<Vec<u8>, Outcome> {
+    let text = match wasmprinter::print_bytes(&data) {
+        Ok(text) => text,
+        Err(e) => return Err(Outcome::rejected(Stage::Print, e)),
+    };
+    match wat::parse_str(&text) {
+        Ok(wasm) => Ok(wasm),
+        Err(e) => Err(Outcome::Disagreement {
+            report: format!("wat rejected the text printed by wasmprinter: {}", e),
+        }),
+    }
 }
@@ -3,34 +3,83 @@ WASMTIME:
 - https://github.com/bytecodealliance/wasmtime
 ***********************************************/
 
-use wasmtime::{Config, Engine, Instance, Module, Store, Strategy};
+use crate::dummy::wasmtime::dummy_imports;
+use crate::execution::{Execution, TrapKind};
+use crate::features::Features;
+use crate::outcome::{Outcome, Stage};
+use crate::wast::Command;
+use wasmtime::{Config, Engine, Func, Instance, Module, Store, Strategy, Trap, Val, ValType};
 
 /// Fuzzing `wasmtime::validate` with default Store/Config/Engine
-pub fn fuzz_wasmtime_validate(data: &[u8]) -> bool {
+pub fn fuzz_wasmtime_validate(data: &[u8]) -> Outcome {
     let store = Store::default();
-    Module::validate(&store.engine(), &data).is_ok()
+    match Module::validate(&store.engine(), &data) {
+        Ok(_) => Outcome::Accepted,
+        Err(e) => Outcome::rejected(Stage::Validate, e),
+    }
 }
 
 /// Fuzzing `wasmtime::validate` with all the features enabled
-pub fn fuzz_wasmtime_validate_all_feat(data: &[u8]) -> bool {
+pub fn fuzz_wasmtime_validate_all_feat(data: &[u8]) -> Outcome {
     let store = match get_store_all_feat(Strategy::Cranelift) {
-        None => return false,
+        None => return Outcome::Unsupported,
         Some(a) => a,
     };
-    Module::validate(&store.engine(), &data).is_ok()
+    match Module::validate(&store.engine(), &data) {
+        Ok(_) => Outcome::Accepted,
+        Err(e) => Outcome::rejected(Stage::Validate, e),
+    }
+}
+
+/// Fuzzing `wasmtime::validate` with the proposals of `features`
+pub fn fuzz_wasmtime_validate_with_features(data: &[u8], features: Features) -> Outcome {
+    let mut config = Config::new();
+    // `wasm_reference_types` also sets bulk memory so it goes first
+    config
+        .wasm_reference_types(features.reference_types)
+        .wasm_bulk_memory(features.bulk_memory)
+        .wasm_simd(features.simd)
+        .wasm_threads(features.threads)
+        .wasm_multi_value(features.multi_value);
+    match Module::validate(&Engine::new(&config), &data) {
+        Ok(_) => Outcome::Accepted,
+        Err(e) => Outcome::rejected(Stage::Validate, e),
+    }
 }
 
 /// Fuzzing `wasmtime::Module` with default Store/Config/Engine
 ///
 /// NOTE: wasmtime::from_binary is also calling wasmtime::validate.
-pub fn fuzz_wasmtime_compile(data: &[u8]) -> bool {
+pub fn fuzz_wasmtime_compile(data: &[u8]) -> Outcome {
     let store = Store::default();
-    Module::from_binary(&store.engine(), &data).is_ok()
+    match Module::from_binary(&store.engine(), &data) {
+        Ok(_) => Outcome::Accepted,
+        Err(e) => Outcome::rejected(Stage::Compile, e),
+    }
 }
 
 /// Return a Store created with the given Strategy and with
 /// all the features enabled
 fn get_store_all_feat(strategy: Strategy) -> Option<Store> {
+    let config = get_config_all_feat(strategy)?;
+    let store = Store::new(&Engine::new(&config));
+    Some(store)
+}
+
+/// Return a Store created like `get_store_all_feat` but consuming fuel,
+/// provided with `fuel_limit` units.
+fn get_store_with_fuel(strategy: Strategy) -> Option<Store> {
+    use crate::execution::fuel_limit;
+
+    let mut config = get_config_all_feat(strategy)?;
+    config.consume_fuel(true);
+    let store = Store::new(&Engine::new(&config));
+    store.add_fuel(fuel_limit()).ok()?;
+    Some(store)
+}
+
+/// Return a Config with the given Strategy and all the features enabled
+fn get_config_all_feat(strategy: Strategy) -> Option<Config> {
     // Create new compilation config
     let mut config = Config::new();
     // Select Cranelift as compiler
@@ -47,57 +96,247 @@ fn get_store_all_feat(strategy: Strategy) -> Option<Store> {
         .wasm_simd(true)
         .wasm_bulk_memory(true)
         .wasm_multi_value(true);
-    let store = Store::new(&Engine::new(&config));
-    Some(store)
+    Some(config)
 }
 
 /// Fuzzing `wasmtime::Module` with all wasm features and `Cranelift` backend.
-pub fn fuzz_wasmtime_compile_all_cranelift(data: &[u8]) -> bool {
+pub fn fuzz_wasmtime_compile_all_cranelift(data: &[u8]) -> Outcome {
     let store = match get_store_all_feat(Strategy::Cranelift) {
-        None => return false,
+        None => return Outcome::Unsupported,
         Some(a) => a,
     };
-    Module::from_binary(&store.engine(), &data).is_ok()
+    match Module::from_binary(&store.engine(), &data) {
+        Ok(_) => Outcome::Accepted,
+        Err(e) => Outcome::rejected(Stage::Compile, e),
+    }
 }
 
 /// Fuzzing `wasmtime::Module` with all wasm features and `Lightbeam` backend.
-pub fn fuzz_wasmtime_compile_all_lightbeam(data: &[u8]) -> bool {
+pub fn fuzz_wasmtime_compile_all_lightbeam(data: &[u8]) -> Outcome {
     let store = match get_store_all_feat(Strategy::Lightbeam) {
-        None => return false,
+        None => return Outcome::Unsupported,
         Some(a) => a,
     };
-    Module::from_binary(&store.engine(), &data).is_ok()
+    match Module::from_binary(&store.engine(), &data) {
+        Ok(_) => Outcome::Accepted,
+        Err(e) => Outcome::rejected(Stage::Compile, e),
+    }
 }
 
-/// Fuzzing `wasmtime::Instance` with all wasm features and `Cranelift` backend.
-pub fn fuzz_wasmtime_instantiate_all_cranelift(data: &[u8]) -> bool {
+/// Fuzzing `wasmtime::Instance` with all wasm features, `Cranelift` backend
+/// and dummy imports.
+pub fn fuzz_wasmtime_instantiate_all_cranelift(data: &[u8]) -> Outcome {
     let store = match get_store_all_feat(Strategy::Cranelift) {
-        None => return false,
+        None => return Outcome::Unsupported,
         Some(a) => a,
     };
     // Create a Module
     let module = match Module::from_binary(&store.engine(), &data) {
         Ok(a) => a,
-        _ => return false,
+        Err(e) => return Outcome::rejected(Stage::Compile, e),
+    };
+    // Create a stub for every import of the module
+    let imports = match dummy_imports(&store, &module) {
+        Some(a) => a,
+        None => return Outcome::Unsupported,
+    };
+    match Instance::new(&store, &module, &imports) {
+        Ok(_) => Outcome::Accepted,
+        Err(e) => instantiation_failure(e),
+    }
+}
+
+/// Outcome of a failed `wasmtime::Instance::new`, a trap coming
+/// from the start function.
+fn instantiation_failure(error: anyhow::Error) -> Outcome {
+    match error.downcast_ref::<Trap>() {
+        Some(trap) => Outcome::Trapped {
+            kind: TrapKind::from_message(&trap.to_string()),
+        },
+        None => Outcome::rejected(Stage::Instantiate, error),
+    }
+}
+
+/// Instantiate the module with all wasm features, `Cranelift` backend and
+/// dummy imports, run the start function and call all exported functions.
+///
+/// NOTE: the execution is bounded by `fuel_limit`.
+pub fn wasmtime_execute_exports(data: &[u8]) -> Execution {
+    use crate::execution::{exported_functions, split_input, ArgsReader, Call};
+
+    let (data, args) = split_input(data);
+
+    let store = match get_store_with_fuel(Strategy::Cranelift) {
+        None => return Execution::Rejected(Outcome::Unsupported),
+        Some(a) => a,
+    };
+    let instance = match instantiate_with_dummies(&store, &data) {
+        Ok(a) => a,
+        Err(execution) => return execution,
+    };
+
+    let mut reader = ArgsReader::new(args);
+    let mut calls = Vec::new();
+    for name in exported_functions(&data) {
+        let func = match instance.get_func(&name) {
+            Some(a) => a,
+            None => continue,
+        };
+        // Skip functions using types unknown to the other runtimes
+        let params: Option<Vec<_>> = func.ty().params().iter().map(to_val_type).collect();
+        let params = match params {
+            Some(a) => a,
+            None => continue,
+        };
+        let args = reader.args(&params);
+        let result = call(&func, &args);
+        calls.push(Call { name, args, result });
+    }
+    Execution::Executed(calls)
+}
+
+/// Instantiate the module in `store` with dummy imports and run
+/// the start function.
+fn instantiate_with_dummies(store: &Store, data: &[u8]) -> Result<Instance, Execution> {
+    let module = match Module::from_binary(&store.engine(), &data) {
+        Ok(a) => a,
+        Err(e) => return Err(Execution::Rejected(Outcome::rejected(Stage::Compile, e))),
     };
-    Instance::new(&store, &module, &[]).is_ok()
-    // TODO(RM4) - check parameter Instance
-    // TODO(RM4) - Execute function of the module
+    let imports = match dummy_imports(&store, &module) {
+        Some(a) => a,
+        None => return Err(Execution::Rejected(Outcome::Unsupported)),
+    };
+    // A trap inside the start function makes the instantiation fail.
+    match Instance::new(&store, &module, &imports) {
+        Ok(a) => Ok(a),
+        Err(e) => Err(match instantiation_failure(e) {
+            Outcome::Trapped { kind } => Execution::StartTrapped(kind),
+            outcome => Execution::Rejected(outcome),
+        }),
+    }
+}
+
+/// Call `func` with `args`.
+fn call(func: &Func, args: &[crate::execution::Value]) -> crate::execution::CallResult {
+    let vals: Vec<Val> = args.iter().map(|v| to_val(*v)).collect();
+    match func.call(&vals) {
+        Ok(results) => results
+            .iter()
+            .map(from_val)
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| TrapKind::Unknown("unsupported result type".to_string())),
+        Err(trap) => Err(TrapKind::from_message(&trap.to_string())),
+    }
 }
 
-/// Fuzzing `wasmtime::Instance` with all wasm features and `Lightbeam` backend.
-pub fn fuzz_wasmtime_instantiate_all_lightbeam(data: &[u8]) -> bool {
+/// Run the commands of a WAST script, returning the result of every
+/// invocation. Modules and invocations that can't be made give `None`.
+///
+/// NOTE: every module has its own store, and so its own fuel, like in
+/// `wasmi_run_commands`.
+pub fn wasmtime_run_commands(commands: &[Command]) -> Vec<Option<crate::execution::CallResult>> {
+    use std::collections::HashMap;
+
+    let mut named = HashMap::new();
+    let mut current: Option<Instance> = None;
+    let mut results = Vec::new();
+    for command in commands {
+        let result = match command {
+            Command::Module { name, wasm } => {
+                current = wasm.as_ref().and_then(|wasm| {
+                    let store = get_store_with_fuel(Strategy::Cranelift)?;
+                    instantiate_with_dummies(&store, &wasm).ok()
+                });
+                if let Some(name) = name {
+                    match &current {
+                        Some(instance) => named.insert(name.clone(), instance.clone()),
+                        None => named.remove(name),
+                    };
+                }
+                None
+            }
+            Command::Invoke { module, name, args } => {
+                let instance = match module {
+                    Some(module) => named.get(module),
+                    None => current.as_ref(),
+                };
+                instance.and_then(|instance| {
+                    let func = instance.get_func(&name)?;
+                    // Skip invocations not matching the signature
+                    let params: Option<Vec<_>> =
+                        func.ty().params().iter().map(to_val_type).collect();
+                    let params = params?;
+                    if params.len() != args.len()
+                        || params.iter().zip(args).any(|(ty, v)| *ty != v.ty())
+                    {
+                        return None;
+                    }
+                    Some(call(&func, &args))
+                })
+            }
+        };
+        results.push(result);
+    }
+    results
+}
+
+/// Convert a `wasmtime::ValType` into a runtime independent type.
+fn to_val_type(ty: &ValType) -> Option<crate::execution::ValType> {
+    use crate::execution::ValType as Ty;
+
+    match ty {
+        ValType::I32 => Some(Ty::I32),
+        ValType::I64 => Some(Ty::I64),
+        ValType::F32 => Some(Ty::F32),
+        ValType::F64 => Some(Ty::F64),
+        _ => None,
+    }
+}
+
+/// Convert a runtime independent value into a `wasmtime::Val`.
+fn to_val(value: crate::execution::Value) -> Val {
+    use crate::execution::Value;
+
+    match value {
+        Value::I32(v) => Val::I32(v),
+        Value::I64(v) => Val::I64(v),
+        Value::F32(bits) => Val::F32(bits),
+        Value::F64(bits) => Val::F64(bits),
+    }
+}
+
+/// Convert a `wasmtime::Val` into a runtime independent value.
+fn from_val(val: &Val) -> Option<crate::execution::Value> {
+    use crate::execution::Value;
+
+    match val {
+        Val::I32(v) => Some(Value::I32(*v)),
+        Val::I64(v) => Some(Value::I64(*v)),
+        Val::F32(bits) => Some(Value::F32(*bits)),
+        Val::F64(bits) => Some(Value::F64(*bits)),
+        _ => None,
+    }
+}
+
+/// Fuzzing `wasmtime::Instance` with all wasm features, `Lightbeam` backend
+/// and dummy imports.
+pub fn fuzz_wasmtime_instantiate_all_lightbeam(data: &[u8]) -> Outcome {
     let store = match get_store_all_feat(Strategy::Lightbeam) {
-        None => return false,
+        None => return Outcome::Unsupported,
         Some(a) => a,
     };
     // Create a Module
     let module = match Module::from_binary(&store.engine(), &data) {
         Ok(a) => a,
-        _ => return false,
+        Err(e) => return Outcome::rejected(Stage::Compile, e),
     };
-    Instance::new(&store, &module, &[]).is_ok()
-
-    // TODO(RM4) - check parameter Instance
-    // TODO(RM4) - Execute function of the module
+    // Create a stub for every import of the module
+    let imports = match dummy_imports(&store, &module) {
+        Some(a) => a,
+        None => return Outcome::Unsupported,
+    };
+    match Instance::new(&store, &module, &imports) {
+        Ok(_) => Outcome::Accepted,
+        Err(e) => instantiation_failure(e),
+    }
 }
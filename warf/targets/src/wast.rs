@@ -3,18 +3,267 @@ wast
 - https://github.com/bytecodealliance/wasm-tools/tree/master/crates/wast
 ************************************************/
 
-pub fn wast_parser(data: &[u8]) -> bool {
+use crate::execution::{CallResult, TrapKind, Value};
+use crate::outcome::{Outcome, Stage};
+
+pub fn wast_parser(data: &[u8]) -> Outcome {
     use wast::parser::{self, ParseBuffer};
     use wast::Wat;
 
     let data = match std::str::from_utf8(&data) {
         Ok(o) => o,
-        Err(_) => return false,
+        Err(e) => return Outcome::rejected(Stage::Parse, e),
     };
 
     let buf = match ParseBuffer::new(&data) {
         Ok(o) => o,
-        Err(_) => return false,
+        Err(e) => return Outcome::rejected(Stage::Parse, e),
     };
-    parser::parse::<Wat>(&buf).is_ok()
+    match parser::parse::<Wat>(&buf) {
+        Ok(_) => Outcome::Accepted,
+        Err(e) => Outcome::rejected(Stage::Parse, e),
+    }
+}
+
+/// Directive of a WAST script executed by the runtimes.
+pub enum Command {
+    /// Instantiate the module and make it the current one.
+    ///
+    /// NOTE: `wasm` is `None` for modules the runtimes can't run
+    /// (e.g. modules with imports), invocations on them are skipped.
+    Module {
+        name: Option<String>,
+        wasm: Option<Vec<u8>>,
+    },
+    /// Call the exported function `name` of `module`, the current
+    /// module if `None`.
+    Invoke {
+        module: Option<String>,
+        name: String,
+        args: Vec<Value>,
+    },
+}
+
+/// Assertion on the result of a command.
+enum Expected {
+    /// No assertion (`module`, `invoke`, or assertion on types
+    /// not handled by the harness).
+    Nothing,
+    /// `assert_return`, `None` matching any NaN.
+    Return(Vec<Option<Value>>),
+    /// `assert_trap`.
+    Trap(TrapKind),
+}
+
+/// Convert the arguments of an invocation, `None` if one of them
+/// is not a constant of a `ValType`.
+fn to_args(args: &[wast::Expression]) -> Option<Vec<Value>> {
+    use wast::Instruction;
+
+    args.iter()
+        .map(|arg| match &*arg.instrs {
+            [Instruction::I32Const(v)] => Some(Value::I32(*v)),
+            [Instruction::I64Const(v)] => Some(Value::I64(*v)),
+            [Instruction::F32Const(v)] => Some(Value::F32(v.bits)),
+            [Instruction::F64Const(v)] => Some(Value::F64(v.bits)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Convert the results of an `assert_return`, `None` if one of them
+/// has a type not handled by the harness.
+fn to_expected(results: &[wast::AssertExpression]) -> Option<Vec<Option<Value>>> {
+    use wast::{AssertExpression, NanPattern};
+
+    results
+        .iter()
+        .map(|result| match result {
+            AssertExpression::I32(v) => Some(Some(Value::I32(*v))),
+            AssertExpression::I64(v) => Some(Some(Value::I64(*v))),
+            AssertExpression::F32(NanPattern::Value(v)) => Some(Some(Value::F32(v.bits))),
+            AssertExpression::F64(NanPattern::Value(v)) => Some(Some(Value::F64(v.bits))),
+            AssertExpression::F32(_) | AssertExpression::F64(_) => Some(None),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Convert an invocation into a command.
+fn invoke_command(invoke: wast::WastInvoke) -> Option<Command> {
+    Some(Command::Invoke {
+        module: invoke.module.map(|id| id.name().to_string()),
+        name: invoke.name.to_string(),
+        args: to_args(&invoke.args)?,
+    })
+}
+
+/// Convert the directives of a WAST script into commands, with the
+/// assertion and the line of each of them.
+///
+/// NOTE: other directives (e.g. `assert_invalid`, `register`) are skipped.
+fn to_commands(text: &str, wast: wast::Wast) -> Vec<(Command, Expected, usize)> {
+    use crate::execution::has_imports;
+    use wast::{WastDirective, WastExecute};
+
+    let mut commands = Vec::new();
+    for directive in wast.directives {
+        let line = directive.span().linecol_in(&text).0 + 1;
+        let command = match directive {
+            WastDirective::Module(mut module) => {
+                let name = module.id.map(|id| id.name().to_string());
+                let wasm = module.encode().ok().filter(|wasm| !has_imports(&wasm));
+                Some((Command::Module { name, wasm }, Expected::Nothing))
+            }
+            WastDirective::Invoke(invoke) => {
+                invoke_command(invoke).map(|command| (command, Expected::Nothing))
+            }
+            WastDirective::AssertReturn {
+                exec: WastExecute::Invoke(invoke),
+                results,
+                ..
+            } => {
+                let expected = match to_expected(&results) {
+                    Some(values) => Expected::Return(values),
+                    None => Expected::Nothing,
+                };
+                invoke_command(invoke).map(|command| (command, expected))
+            }
+            WastDirective::AssertTrap {
+                exec: WastExecute::Invoke(invoke),
+                message,
+                ..
+            } => invoke_command(invoke)
+                .map(|command| (command, Expected::Trap(TrapKind::from_message(message)))),
+            _ => None,
+        };
+        if let Some((command, expected)) = command {
+            commands.push((command, expected, line));
+        }
+    }
+    commands
+}
+
+/// Index of the `module` command instantiating the instance used by
+/// each command, `None` if there is no such module.
+fn instance_indices(commands: &[Command]) -> Vec<Option<usize>> {
+    use std::collections::HashMap;
+
+    let mut named = HashMap::new();
+    let mut current = None;
+    commands
+        .iter()
+        .enumerate()
+        .map(|(index, command)| match command {
+            Command::Module { name, .. } => {
+                current = Some(index);
+                if let Some(name) = name {
+                    named.insert(name.as_str(), index);
+                }
+                current
+            }
+            Command::Invoke { module: None, .. } => current,
+            Command::Invoke {
+                module: Some(module),
+                ..
+            } => named.get(module.as_str()).copied(),
+        })
+        .collect()
+}
+
+/// Check a result against its assertion.
+fn check(expected: &Expected, result: &CallResult) -> bool {
+    match (expected, result) {
+        (Expected::Nothing, _) => true,
+        (Expected::Return(values), Ok(results)) => {
+            values.len() == results.len()
+                && values
+                    .iter()
+                    .zip(results)
+                    .all(|(value, result)| match value {
+                        Some(value) => value == result,
+                        None => match result {
+                            Value::F32(bits) => f32::from_bits(*bits).is_nan(),
+                            Value::F64(bits) => f64::from_bits(*bits).is_nan(),
+                            _ => false,
+                        },
+                    })
+        }
+        (Expected::Return(_), Err(_)) => false,
+        (Expected::Trap(_), Ok(_)) => false,
+        (Expected::Trap(expected), Err(trap)) => expected == trap || !expected.is_comparable(),
+    }
+}
+
+/// Fuzzing wasmi and wasmtime with WAST scripts: run the `module`,
+/// `invoke`, `assert_return` and `assert_trap` directives with both
+/// runtimes and report the results not matching the assertions.
+///
+/// NOTE: a trap specific to a runtime (e.g. out of fuel) is inconclusive,
+/// it is listed in the report and the state of the instance can't be
+/// trusted anymore: the following invocations of this instance are
+/// skipped for this runtime.
+pub fn wast_execute(data: &[u8]) -> Outcome {
+    use crate::{wasmi, wasmtime};
+    use std::collections::HashSet;
+    use std::fmt::Write;
+    use wast::parser::{self, ParseBuffer};
+    use wast::Wast;
+
+    let text = match std::str::from_utf8(&data) {
+        Ok(o) => o,
+        Err(e) => return Outcome::rejected(Stage::Parse, e),
+    };
+    let buf = match ParseBuffer::new(&text) {
+        Ok(o) => o,
+        Err(e) => return Outcome::rejected(Stage::Parse, e),
+    };
+    let wast = match parser::parse::<Wast>(&buf) {
+        Ok(o) => o,
+        Err(e) => return Outcome::rejected(Stage::Parse, e),
+    };
+
+    let (commands, assertions): (Vec<Command>, Vec<(Expected, usize)>) = to_commands(&text, wast)
+        .into_iter()
+        .map(|(command, expected, line)| (command, (expected, line)))
+        .unzip();
+    let runs = [
+        ("wasmi", wasmi::wasmi_run_commands(&commands)),
+        ("wasmtime", wasmtime::wasmtime_run_commands(&commands)),
+    ];
+
+    let instances = instance_indices(&commands);
+    let mut report = String::new();
+    let mut inconclusive = String::new();
+    for (runtime, results) in &runs {
+        let mut skipped = HashSet::new();
+        for (((expected, line), result), instance) in assertions.iter().zip(results).zip(&instances)
+        {
+            let result = match result {
+                Some(result) => result,
+                None => continue,
+            };
+            if instance.map_or(false, |i| skipped.contains(&i)) {
+                continue;
+            }
+            if let Err(trap) = result {
+                if !trap.is_comparable() {
+                    let _ = writeln!(inconclusive, "  {}: line {} => {:?}", runtime, line, trap);
+                    skipped.extend(instance);
+                    continue;
+                }
+            }
+            if !check(expected, result) {
+                let _ = writeln!(report, "  {}: line {} => {:?}", runtime, line, result);
+            }
+        }
+    }
+    if report.is_empty() {
+        Outcome::Accepted
+    } else {
+        if !inconclusive.is_empty() {
+            let _ = write!(report, "inconclusive:\n{}", inconclusive);
+        }
+        Outcome::Disagreement { report }
+    }
 }
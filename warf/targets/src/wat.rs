@@ -3,10 +3,15 @@ wat
 - https://github.com/bytecodealliance/wasm-tools/tree/master/crates/wat
 ************************************************/
 
-pub fn wat_parser(data: &[u8]) -> bool {
+use crate::outcome::{Outcome, Stage};
+
+pub fn wat_parser(data: &[u8]) -> Outcome {
     let data = match std::str::from_utf8(&data) {
         Ok(o) => o,
-        Err(_) => return false,
+        Err(e) => return Outcome::rejected(Stage::Parse, e),
     };
-    wat::parse_str(&data).is_ok()
+    match wat::parse_str(&data) {
+        Ok(_) => Outcome::Accepted,
+        Err(e) => Outcome::rejected(Stage::Parse, e),
+    }
 }